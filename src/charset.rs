@@ -0,0 +1,216 @@
+use crate::definitions::StandardCharset;
+
+/// Decodes bytes 0x80-0xFF when the input isn't UTF-8
+pub type CodePage = Box<dyn Fn(u8) -> char + Send>;
+
+/// Positions the national replacement sets change: `# @ [ \ ] ^ _ ` { | } ~`
+const NRC_POSITIONS: &str = "#@[\\]^_`{|}~";
+
+// Replacements for `NRC_POSITIONS`, from the VT220 manual
+const UK: &str = "£@[\\]^_`{|}~";
+const DUTCH: &str = "£¾ĳ½|^_`¨ƒ¼´";
+const FINNISH: &str = "#@ÄÖÅÜ_éäöåü";
+const FRENCH: &str = "£à°ç§^_`éùè¨";
+const FRENCH_CANADIAN: &str = "#àâçêî_ôéùèû";
+const GERMAN: &str = "#§ÄÖÜ^_`äöüß";
+const ITALIAN: &str = "£§°çé^_ùàòèì";
+const NORWEGIAN_DANISH: &str = "#ÄÆØÅÜ_äæøåü";
+const PORTUGUESE: &str = "#@ÃÇÕ^_`ãçõ~";
+const SPANISH: &str = "£§¡Ñ¿^_`°ñç~";
+const SWEDISH: &str = "#ÉÄÖÅÜ_éäöåü";
+const SWISS: &str = "ùàéçêîèôäöüû";
+
+/// DEC Technical, 0x21 to 0x7E. `\0` marks reserved positions, which are left alone.
+const DEC_TECHNICAL: &str = concat!(
+    "⎷┌─⌠⌡│⎡⎣⎤⎦⎛⎝⎞⎠⎨",
+    "⎬⎲⎳╲╱⌝⌟⟩\0\0\0\0≤≠≥∫",
+    "∴∝∞÷Δ∇ΦΓ∼≃Θ×Λ⇔⇒≡",
+    "ΠΨ\0Σ\0\0√ΩΞΥ⊂⊃∩∪∧∨",
+    "¬αβχδεφγηιθκλ\0ν∂",
+    "πψρστ\0ƒωξυζ←↑→↓",
+);
+
+impl StandardCharset {
+    /// Map a character from the GL range (0x20-0x7E) according to the charset
+    pub fn map(self, c: char) -> char {
+        match self {
+            StandardCharset::Ascii => c,
+            StandardCharset::SpecialCharacterAndLineDrawing => match c {
+                '_' => ' ',
+                '`' => '◆',
+                'a' => '▒',
+                'b' => '\u{2409}', // Symbol for horizontal tabulation
+                'c' => '\u{240c}', // Symbol for form feed
+                'd' => '\u{240d}', // Symbol for carriage return
+                'e' => '\u{240a}', // Symbol for line feed
+                'f' => '°',
+                'g' => '±',
+                'h' => '\u{2424}', // Symbol for newline
+                'i' => '\u{240b}', // Symbol for vertical tabulation
+                'j' => '┘',
+                'k' => '┐',
+                'l' => '┌',
+                'm' => '└',
+                'n' => '┼',
+                'o' => '⎺',
+                'p' => '⎻',
+                'q' => '─',
+                'r' => '⎼',
+                's' => '⎽',
+                't' => '├',
+                'u' => '┤',
+                'v' => '┴',
+                'w' => '┬',
+                'x' => '│',
+                'y' => '≤',
+                'z' => '≥',
+                '{' => 'π',
+                '|' => '≠',
+                '}' => '£',
+                '~' => '·',
+                _ => c,
+            },
+            StandardCharset::DecSupplemental => match c {
+                '(' => '¤',
+                'W' => 'Œ',
+                ']' => 'Ÿ',
+                'w' => 'œ',
+                '}' => 'ÿ',
+                // Otherwise the same as the upper half of Latin-1
+                _ => upper_half(c),
+            },
+            StandardCharset::DecTechnical => match c {
+                '!'..='~' => DEC_TECHNICAL
+                    .chars()
+                    .nth(c as usize - 0x21)
+                    .filter(|&mapped| mapped != '\0')
+                    .unwrap_or(c),
+                _ => c,
+            },
+            StandardCharset::Latin1Supplemental => upper_half(c),
+            StandardCharset::Uk => replace_national(c, UK),
+            StandardCharset::Dutch => replace_national(c, DUTCH),
+            StandardCharset::Finnish => replace_national(c, FINNISH),
+            StandardCharset::French => replace_national(c, FRENCH),
+            StandardCharset::FrenchCanadian => replace_national(c, FRENCH_CANADIAN),
+            StandardCharset::German => replace_national(c, GERMAN),
+            StandardCharset::Italian => replace_national(c, ITALIAN),
+            StandardCharset::NorwegianDanish => replace_national(c, NORWEGIAN_DANISH),
+            StandardCharset::Portuguese => replace_national(c, PORTUGUESE),
+            StandardCharset::Spanish => replace_national(c, SPANISH),
+            StandardCharset::Swedish => replace_national(c, SWEDISH),
+            StandardCharset::Swiss => replace_national(c, SWISS),
+        }
+    }
+
+    /// Map a character from the GR range (U+00A0-U+00FF) according to the charset.
+    /// Only the supplemental and graphics sets change it.
+    pub fn map_gr(self, c: char) -> char {
+        if self.is_national() || self == StandardCharset::Ascii {
+            return c;
+        }
+        let Some(gl) = (c as u32).checked_sub(0x80).and_then(char::from_u32).filter(|gl| ('!'..='~').contains(gl))
+        else {
+            return c;
+        };
+        match self.map(gl) {
+            mapped if mapped == gl => c,
+            mapped => mapped,
+        }
+    }
+
+    /// 96-character sets are designated with `ESC - . /` and can't go into G0
+    pub fn is_96_character(self) -> bool {
+        self == StandardCharset::Latin1Supplemental
+    }
+
+    /// Whether designating the set requires DECNRCM mode
+    pub fn is_national(self) -> bool {
+        matches!(
+            self,
+            StandardCharset::Dutch
+                | StandardCharset::Finnish
+                | StandardCharset::French
+                | StandardCharset::FrenchCanadian
+                | StandardCharset::German
+                | StandardCharset::Italian
+                | StandardCharset::NorwegianDanish
+                | StandardCharset::Portuguese
+                | StandardCharset::Spanish
+                | StandardCharset::Swedish
+                | StandardCharset::Swiss
+        )
+    }
+
+    /// Final byte (with a `%` intermediate for some sets) that designates the set
+    pub(crate) fn designator(self) -> &'static [u8] {
+        match self {
+            StandardCharset::Ascii => b"B",
+            StandardCharset::SpecialCharacterAndLineDrawing => b"0",
+            StandardCharset::Uk => b"A",
+            StandardCharset::DecSupplemental => b"%5",
+            StandardCharset::DecTechnical => b">",
+            StandardCharset::Latin1Supplemental => b"A",
+            StandardCharset::Dutch => b"4",
+            StandardCharset::Finnish => b"C",
+            StandardCharset::French => b"R",
+            StandardCharset::FrenchCanadian => b"Q",
+            StandardCharset::German => b"K",
+            StandardCharset::Italian => b"Y",
+            StandardCharset::NorwegianDanish => b"E",
+            StandardCharset::Portuguese => b"%6",
+            StandardCharset::Spanish => b"Z",
+            StandardCharset::Swedish => b"H",
+            StandardCharset::Swiss => b"=",
+        }
+    }
+
+    /// 94-character set for the designator after `ESC ( ) * +`, including the
+    /// alternative final bytes DEC terminals accept
+    pub(crate) fn from_designator(intermediates: &[u8], final_byte: u8) -> Option<StandardCharset> {
+        let charset = match (intermediates, final_byte) {
+            (b"", b'B') => StandardCharset::Ascii,
+            (b"", b'0') => StandardCharset::SpecialCharacterAndLineDrawing,
+            (b"", b'A') => StandardCharset::Uk,
+            // `<` is the user-preferred supplemental set
+            (b"", b'<') | (b"%", b'5') => StandardCharset::DecSupplemental,
+            (b"", b'>') => StandardCharset::DecTechnical,
+            (b"", b'4') => StandardCharset::Dutch,
+            (b"", b'C' | b'5') => StandardCharset::Finnish,
+            (b"", b'R' | b'f') => StandardCharset::French,
+            (b"", b'Q' | b'9') => StandardCharset::FrenchCanadian,
+            (b"", b'K') => StandardCharset::German,
+            (b"", b'Y') => StandardCharset::Italian,
+            (b"", b'E' | b'6' | b'`') => StandardCharset::NorwegianDanish,
+            (b"%", b'6') => StandardCharset::Portuguese,
+            (b"", b'Z') => StandardCharset::Spanish,
+            (b"", b'H' | b'7') => StandardCharset::Swedish,
+            (b"", b'=') => StandardCharset::Swiss,
+            _ => return None,
+        };
+        Some(charset)
+    }
+
+    /// 96-character set for the designator after `ESC - . /`
+    pub(crate) fn from_96_designator(intermediates: &[u8], final_byte: u8) -> Option<StandardCharset> {
+        match (intermediates, final_byte) {
+            (b"", b'A') => Some(StandardCharset::Latin1Supplemental),
+            _ => None,
+        }
+    }
+}
+
+fn upper_half(c: char) -> char {
+    match c {
+        '!'..='~' => char::from(c as u8 + 0x80),
+        _ => c,
+    }
+}
+
+fn replace_national(c: char, replacements: &str) -> char {
+    NRC_POSITIONS
+        .chars()
+        .position(|position| position == c)
+        .and_then(|index| replacements.chars().nth(index))
+        .unwrap_or(c)
+}
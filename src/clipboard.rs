@@ -0,0 +1,150 @@
+use log::warn;
+
+use crate::definitions::{Selection, StringTerminator, TerminalOutput};
+
+/// Kind of clipboard access requested by an OSC 52 sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardAccess {
+    /// A query that would send the clipboard contents back to the application
+    Read,
+    /// Setting or clearing the clipboard
+    Write,
+}
+
+/// Decides whether an OSC 52 request is honoured
+pub type ClipboardPolicy = Box<dyn Fn(ClipboardAccess, &[Selection]) -> bool + Send>;
+
+/// Default limit for decoded OSC 52 data
+pub const DEFAULT_CLIPBOARD_MAX_SIZE: usize = 1 << 20;
+
+pub(crate) struct ClipboardConfig {
+    pub(crate) max_size: usize,
+    pub(crate) policy: Option<ClipboardPolicy>,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        ClipboardConfig {
+            max_size: DEFAULT_CLIPBOARD_MAX_SIZE,
+            policy: None,
+        }
+    }
+}
+
+impl ClipboardConfig {
+    fn allows(&self, access: ClipboardAccess, selection: &[Selection]) -> bool {
+        self.policy.as_ref().is_none_or(|policy| policy(access, selection))
+    }
+
+    /// OSC 52 ; Pc ; Pd. Returns `None` when the request is rejected.
+    pub(crate) fn parse(&self, params: &str, terminator: StringTerminator) -> Option<TerminalOutput> {
+        let (targets, data) = params.split_once(';')?;
+        let selection = parse_selection(targets);
+
+        if data == "?" {
+            if !self.allows(ClipboardAccess::Read, &selection) {
+                warn!("OSC 52 clipboard read denied by policy");
+                return None;
+            }
+            return Some(TerminalOutput::ClipboardQuery { selection, terminator });
+        }
+
+        if !self.allows(ClipboardAccess::Write, &selection) {
+            warn!("OSC 52 clipboard write denied by policy");
+            return None;
+        }
+        // Check the encoded length first so oversized payloads are never decoded
+        if data.len() / 4 * 3 > self.max_size.saturating_add(2) {
+            warn!("OSC 52 payload exceeds the {} byte limit", self.max_size);
+            return None;
+        }
+        // Anything that isn't valid base64 clears the selection, as in xterm
+        match base64_decode(data) {
+            Some(data) if data.len() > self.max_size => {
+                warn!("OSC 52 payload exceeds the {} byte limit", self.max_size);
+                None
+            }
+            Some(data) if !data.is_empty() => Some(TerminalOutput::ClipboardSet { selection, data }),
+            _ => Some(TerminalOutput::ClipboardClear { selection }),
+        }
+    }
+}
+
+/// Parse the Pc parameter. An empty value means xterm's default of `s0`.
+fn parse_selection(targets: &str) -> Vec<Selection> {
+    if targets.is_empty() {
+        return vec![Selection::Select, Selection::CutBuffer(0)];
+    }
+    targets.bytes().filter_map(Selection::from_byte).collect()
+}
+
+impl Selection {
+    pub fn from_byte(byte: u8) -> Option<Selection> {
+        match byte {
+            b'c' => Some(Selection::Clipboard),
+            b'p' => Some(Selection::Primary),
+            b'q' => Some(Selection::Secondary),
+            b's' => Some(Selection::Select),
+            b'0'..=b'7' => Some(Selection::CutBuffer(byte - b'0')),
+            _ => None,
+        }
+    }
+
+    pub fn as_byte(self) -> u8 {
+        match self {
+            Selection::Clipboard => b'c',
+            Selection::Primary => b'p',
+            Selection::Secondary => b'q',
+            Selection::Select => b's',
+            Selection::CutBuffer(n) => b'0' + n,
+        }
+    }
+}
+
+/// Reply to an OSC 52 query: `OSC 52 ; Pc ; base64(data) ST`
+pub fn clipboard_reply(selection: &[Selection], data: &[u8], terminator: StringTerminator) -> Vec<u8> {
+    let mut reply = b"\x1B]52;".to_vec();
+    reply.extend(selection.iter().map(|s| s.as_byte()));
+    reply.push(b';');
+    reply.extend_from_slice(base64_encode(data).as_bytes());
+    reply.extend_from_slice(terminator.as_bytes());
+    reply
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decode standard base64. Padding is optional; any other stray character fails.
+pub(crate) fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=');
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3 + 2);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for byte in encoded.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|&b| b == byte)? as u32;
+        acc = acc << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    // A single leftover character can't encode a whole byte
+    (bits < 6).then_some(decoded)
+}
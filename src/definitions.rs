@@ -1,3 +1,5 @@
+use crate::handlers::ExtensionEvent;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Mode {
     Decckm,
@@ -5,9 +7,70 @@ pub enum Mode {
     ModifyOtherKeys,
     Insert,
     LineFeedNewLine,
+    /// DECANM; resetting it switches to VT52 mode
+    Ansi,
+    /// DECNKM, also set by DECKPAM (`ESC =`) and reset by DECKPNM (`ESC >`)
+    KeypadApplication,
+    /// DECNRCM, enables the national replacement character sets
+    NationalReplacement,
+    /// DECCOLM, 132 columns
+    Columns132,
+    /// DECSCNM
+    ReverseVideo,
+    /// DECOM, cursor addressing relative to the scrolling margins
+    Origin,
+    /// DECAWM
+    AutoWrap,
+    /// Backspace wraps to the previous line (45)
+    ReverseWraparound,
+    /// Reverse wraparound that also crosses the top of the screen (1045)
+    ExtendedReverseWraparound,
+    /// Alternate screen buffer (47)
+    AltScreen,
+    /// Alternate screen buffer, cleared when leaving it (1047)
+    AltScreenClear,
+    /// Save the cursor on set, restore it on reset (1048)
+    SaveCursor,
+    /// DECBKM, the backarrow key sends BS instead of DEL
+    BackarrowKey,
+    /// DECLRMM, enables left/right margins
+    LeftRightMargin,
+    /// Report focus changes with `CSI I` / `CSI O` (1004)
+    FocusReporting,
+    /// Any other mode, with its parameter as sent (e.g. `?9`)
     Unknown(Vec<u8>),
 }
 
+impl Mode {
+    /// The mode for an SM/RM (`private` false) or DECSET/DECRST parameter
+    pub fn from_param(param: usize, private: bool) -> Mode {
+        match (private, param) {
+            (false, 4) => Mode::Insert,
+            (false, 20) => Mode::LineFeedNewLine,
+            (true, 1) => Mode::Decckm,
+            (true, 2) => Mode::Ansi,
+            (true, 3) => Mode::Columns132,
+            (true, 5) => Mode::ReverseVideo,
+            (true, 6) => Mode::Origin,
+            (true, 7) => Mode::AutoWrap,
+            (true, 42) => Mode::NationalReplacement,
+            (true, 45) => Mode::ReverseWraparound,
+            (true, 47) => Mode::AltScreen,
+            (true, 66) => Mode::KeypadApplication,
+            (true, 67) => Mode::BackarrowKey,
+            (true, 69) => Mode::LeftRightMargin,
+            (true, 1004) => Mode::FocusReporting,
+            (true, 1037) => Mode::ModifyOtherKeys,
+            (true, 1045) => Mode::ExtendedReverseWraparound,
+            (true, 1047) => Mode::AltScreenClear,
+            (true, 1048) => Mode::SaveCursor,
+            (true, 2004) => Mode::BracketedPaste,
+            (true, _) => Mode::Unknown(format!("?{}", param).into_bytes()),
+            (false, _) => Mode::Unknown(param.to_string().into_bytes()),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SelectGraphicRendition {
     Reset,
@@ -106,6 +169,9 @@ pub enum TerminalOutput {
     /// Operating System Command (e.g., window title).
     /// Format: `command` ID (e.g., 0, 52) and the payload bytes.
     Osc { command: usize, payload: Vec<u8> },
+    /// OSC whose command is not a number (e.g. `OSC I file ST`).
+    /// `command` is everything before the first `;`.
+    NamedOsc { command: Vec<u8>, payload: Vec<u8> },
     /// Device Control String (e.g., Sixel images, terminfo requests).
     /// Contains the raw data bytes inside the DCS sequence.
     DeviceControlString {
@@ -124,7 +190,23 @@ pub enum TerminalOutput {
     Delete(usize),
     InsertSpaces(usize),
     Backspace,
+    /// HT; tab stops are up to the renderer
+    HorizontalTab,
+    /// BEL
+    Bell,
+    /// ENQ. The answerback message, if any, is queued as a reply.
+    Enquiry,
     Newline,
+    /// VT, a line feed unless a renderer gives it its own meaning
+    VerticalTab,
+    /// FF, a line feed unless a renderer gives it its own meaning
+    FormFeed,
+    /// IND (`ESC D`): move down, scrolling the region up at the bottom margin
+    Index,
+    /// RI (`ESC M`): move up, scrolling the region down at the top margin
+    ReverseIndex,
+    /// NEL (`ESC E`): index and carriage return
+    NextLine,
     CarriageReturn,
     Sgr(SelectGraphicRendition),
     SetCursorVisibility(bool),
@@ -134,7 +216,49 @@ pub enum TerminalOutput {
     ExitAltScreen,
     Invalid,
     DeviceControl { code: u8 },
-    SetScrollingRegion { top: usize, bottom: Option<usize> },
+    /// DECSTBM. `None` (omitted or 0) means the first or last line.
+    SetScrollingRegion { top: Option<usize>, bottom: Option<usize> },
+    /// DECSLRM (`CSI Pl ; Pr s` in DECLRMM mode). `None` (omitted or 0) means
+    /// the first or last column.
+    SetLeftRightMargins { left: Option<usize>, right: Option<usize> },
+    /// DECSC (`ESC 7`), or SCOSC (`CSI s`) outside DECLRMM mode
+    SaveCursor,
+    /// DECRC (`ESC 8`) or SCORC (`CSI u`)
+    RestoreCursor,
+    /// DECCRA (`CSI Pts ; Pls ; Pbs ; Prs ; Pps ; Ptd ; Pld ; Ppd $ v`): copy
+    /// `source` so that its top-left corner lands on `top`/`left`. Pages are ignored.
+    CopyRectangle { source: Rect, top: usize, left: usize },
+    /// DECFRA (`CSI Pch ; Pt ; Pl ; Pb ; Pr $ x`)
+    FillRectangle { ch: char, area: Rect },
+    /// DECERA (`CSI Pt ; Pl ; Pb ; Pr $ z`)
+    EraseRectangle(Rect),
+    /// DECSERA (`CSI Pt ; Pl ; Pb ; Pr $ {`): erase, sparing protected characters
+    SelectiveEraseRectangle(Rect),
+    /// DECCARA (`CSI Pt ; Pl ; Pb ; Pr ; Ps... $ r`). `attributes` holds only
+    /// `Reset`, `Bold`, `Underline`, `BlinkSlow`, `Reverse` and their negations
+    /// (`NormalIntensity`, `NotUnderline`, `Unknown(25)`, `Unknown(27)`).
+    ChangeRectangleAttributes { area: Rect, attributes: Vec<SelectGraphicRendition> },
+    /// DECRARA (`CSI Pt ; Pl ; Pb ; Pr ; Ps... $ t`): toggle attributes, where
+    /// `Reset` stands for all of `Bold`, `Underline`, `BlinkSlow` and `Reverse`
+    ReverseRectangleAttributes { area: Rect, attributes: Vec<SelectGraphicRendition> },
+    /// DECRQCRA (`CSI Pi ; Pg ; Pt ; Pl ; Pb ; Pr * y`): request a checksum of
+    /// the area on page `page`, answered with `DCS Pi ! ~ xxxx ST`
+    RequestChecksum { id: usize, page: usize, area: Rect },
+    /// DECSCA (`CSI Ps " q`): whether following characters are protected from
+    /// selective erase
+    SetCharacterProtection(bool),
+    /// SPA (`ESC V`): start of an ISO guarded area, protected from every erase
+    StartGuardedArea,
+    /// EPA (`ESC W`)
+    EndGuardedArea,
+    /// DECSED (`CSI ? Ps J`): erase unprotected characters in the display
+    SelectiveEraseDisplay(EraseRange),
+    /// DECSEL (`CSI ? Ps K`): erase unprotected characters in the line
+    SelectiveEraseLine(EraseRange),
+    /// RIS (`ESC c`). The parser has already returned to its initial state.
+    FullReset,
+    /// DECSTR (`CSI ! p`). The parser has already reset its charsets.
+    SoftReset,
     BeginSynchronizedUpdate,
     EndSynchronizedUpdate,
     SetCursorStyle { shape: CursorShape, blinking: bool },
@@ -146,10 +270,68 @@ pub enum TerminalOutput {
     SetActiveCharset(CharsetIndex),
     /// Configure charset designation
     ConfigureCharset { index: CharsetIndex, charset: StandardCharset },
+    /// Invoke a charset into GR (LS1R, LS2R, LS3R)
+    SetActiveGrCharset(CharsetIndex),
+    /// Use G2 or G3 for the next character only (SS2, SS3)
+    SingleShift(CharsetIndex),
     SetDoubleHeightLine { top_half: bool },
     SetDoubleWidthLine,
     SetSingleWidthLine,
+    /// DECALN (`ESC # 8`): fill the screen with `E` for alignment
+    ScreenAlignmentTest,
+    /// OSC 4: change a palette entry
+    SetPaletteColor { index: u8, color: Rgb },
+    /// OSC 4 with `?` as the spec: report a palette entry
+    QueryPaletteColor { index: u8, terminator: StringTerminator },
+    /// OSC 104: reset one palette entry, or the whole palette for `None`
+    ResetPaletteColor(Option<u8>),
+    /// OSC 10/11/12: change the default foreground, background or cursor colour
+    SetDynamicColor { kind: DynamicColor, color: Rgb },
+    /// OSC 10/11/12 with `?` as the spec: report the colour
+    QueryDynamicColor { kind: DynamicColor, terminator: StringTerminator },
+    /// OSC 110/111/112: restore the configured default
+    ResetDynamicColor(DynamicColor),
+    /// OSC 52: replace the contents of the selections with decoded data
+    ClipboardSet { selection: Vec<Selection>, data: Vec<u8> },
+    /// OSC 52 with `?` as the data: report the selection contents
+    ClipboardQuery { selection: Vec<Selection>, terminator: StringTerminator },
+    /// OSC 52 with empty or invalid data: clear the selections
+    ClipboardClear { selection: Vec<Selection> },
+    /// OSC 133/633 `A`: the shell is about to draw the prompt
+    PromptStart,
+    /// OSC 133/633 `B`: the prompt ended and command input begins
+    CommandStart,
+    /// OSC 133/633 `C`: the command line was submitted and output follows
+    CommandExecuted,
+    /// OSC 133/633 `D [; exit]`: the command finished
+    CommandFinished { exit_code: Option<i32> },
+    /// OSC 7 (or iTerm2's `OSC 1337 ; CurrentDir=`): the shell's working directory.
+    /// `path` is percent-decoded and may not be UTF-8.
+    WorkingDirectory { host: Option<String>, path: Vec<u8> },
+    /// Desktop notification from OSC 9, OSC 777 or OSC 99. `id` is only set by
+    /// kitty's OSC 99, which can also carry a title alone.
+    Notification { title: Option<String>, body: String, id: Option<String>, urgency: Urgency },
+    /// OSC 9 ; 4: taskbar-style progress report
+    Progress { state: ProgressState, percent: Option<u8> },
+    /// OSC 2 (and OSC 0): set the window title
+    SetTitle(String),
+    /// OSC 1 (and OSC 0): set the icon name
+    SetIconName(String),
+    /// `CSI 22 ; Ps t`: save the title and/or icon name on the title stack
+    PushTitle(TitleTarget),
+    /// `CSI 23 ; Ps t`: restore the title and/or icon name from the title stack
+    PopTitle(TitleTarget),
+    /// XTWINOPS (`CSI Ps ; Ps ; Ps t`) other than the title stack operations
+    WindowOp(WindowOp),
+    /// `ESC SP F` / `ESC SP G`: how replies should encode C1 controls
+    SetControlEncoding(ControlEncoding),
+    /// `ESC % G` / `ESC % @`: how the input encodes characters
+    SetInputEncoding(InputEncoding),
+    /// A custom event from a handler registered on the parser
+    Extension(Box<dyn ExtensionEvent>),
 }
+/// A styled span of text. `start` and `end` are byte offsets into the text
+/// the tag was produced for.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FormatTag {
     pub start: usize,
@@ -159,9 +341,258 @@ pub struct FormatTag {
     pub bg_color: SelectGraphicRendition,
     pub bold: bool,
     pub italic: bool,
+    pub underline: bool,
     pub url: Option<String>,
 }
 
+/// Colours controlled by OSC 10, 11 and 12
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynamicColor {
+    Foreground,
+    Background,
+    Cursor,
+}
+
+impl DynamicColor {
+    /// OSC number used to set or query this colour; the reset is this plus 100
+    pub fn osc_number(self) -> usize {
+        match self {
+            DynamicColor::Foreground => 10,
+            DynamicColor::Background => 11,
+            DynamicColor::Cursor => 12,
+        }
+    }
+}
+
+/// Selection targets of OSC 52
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Selection {
+    /// `c`
+    Clipboard,
+    /// `p`
+    Primary,
+    /// `q`
+    Secondary,
+    /// `s`, the configurable selection (usually primary or clipboard)
+    Select,
+    /// `0` to `7`
+    CutBuffer(u8),
+}
+
+/// What `CSI 22/23 ; Ps t` saves or restores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleTarget {
+    /// `0`
+    #[default]
+    Both,
+    /// `1`
+    IconName,
+    /// `2`
+    Title,
+}
+
+impl TitleTarget {
+    pub fn from_param(param: usize) -> Option<TitleTarget> {
+        match param {
+            0 => Some(TitleTarget::Both),
+            1 => Some(TitleTarget::IconName),
+            2 => Some(TitleTarget::Title),
+            _ => None,
+        }
+    }
+
+    pub fn includes_title(self) -> bool {
+        self != TitleTarget::IconName
+    }
+
+    pub fn includes_icon_name(self) -> bool {
+        self != TitleTarget::Title
+    }
+}
+
+/// Window manipulations and reports of XTWINOPS, named after xterm's documentation.
+/// Sizes of `None` (sent as `0` or omitted) keep the current value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowOp {
+    /// `1`
+    Deiconify,
+    /// `2`
+    Iconify,
+    /// `3 ; x ; y`: move the window to pixel position x, y
+    Move { x: usize, y: usize },
+    /// `4 ; height ; width`: resize the window in pixels
+    ResizePixels { width: Option<usize>, height: Option<usize> },
+    /// `5`
+    Raise,
+    /// `6`
+    Lower,
+    /// `7`
+    Refresh,
+    /// `8 ; rows ; columns`: resize the text area in characters
+    ResizeChars { columns: Option<usize>, rows: Option<usize> },
+    /// `9 ; Ps`
+    Maximize(MaximizeMode),
+    /// `10 ; Ps`
+    Fullscreen(FullscreenMode),
+    /// `11`: report whether the window is iconified
+    ReportState,
+    /// `13`, or `13 ; 2` for the text area: report the position
+    ReportPosition { text_area: bool },
+    /// `14`, or `14 ; 2` for the whole window: report the size in pixels
+    ReportSizePixels { text_area: bool },
+    /// `15`: report the screen size in pixels
+    ReportScreenSizePixels,
+    /// `16`: report the character cell size in pixels
+    ReportCellSizePixels,
+    /// `18`: report the text area size in characters
+    ReportSizeChars,
+    /// `19`: report the screen size in characters
+    ReportScreenSizeChars,
+    /// `20`
+    ReportIconName,
+    /// `21`
+    ReportTitle,
+    /// `24` and above (DECSLPP): resize to this many lines
+    ResizeLines(usize),
+}
+
+impl WindowOp {
+    /// Size, position and state reports, which can't disturb the user or leak text
+    pub fn is_safe(&self) -> bool {
+        matches!(
+            self,
+            WindowOp::ReportState
+                | WindowOp::ReportPosition { .. }
+                | WindowOp::ReportSizePixels { .. }
+                | WindowOp::ReportScreenSizePixels
+                | WindowOp::ReportCellSizePixels
+                | WindowOp::ReportSizeChars
+                | WindowOp::ReportScreenSizeChars
+        )
+    }
+}
+
+/// `CSI 9 ; Ps t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaximizeMode {
+    /// `0`
+    Restore,
+    /// `1`
+    Both,
+    /// `2`
+    Vertically,
+    /// `3`
+    Horizontally,
+}
+
+/// `CSI 10 ; Ps t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// `0`
+    Exit,
+    /// `1`
+    Enter,
+    /// `2`
+    Toggle,
+}
+
+/// Whether replies use 7-bit (`ESC [`) or 8-bit (0x9B) C1 controls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlEncoding {
+    /// S7C1T
+    #[default]
+    SevenBit,
+    /// S8C1T
+    EightBit,
+}
+
+/// Area of a rectangular operation in 1-based, inclusive coordinates, relative
+/// to the margins in origin mode. `None` (omitted or 0) for `bottom`/`right`
+/// means the last line/column; screens clamp the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: Option<usize>,
+    pub right: Option<usize>,
+}
+
+impl Rect {
+    /// `Pt ; Pl ; Pb ; Pr` with the defaults filled in
+    pub fn from_params(params: &[usize]) -> Rect {
+        let param = |index: usize| params.get(index).copied().filter(|&value| value != 0);
+        Rect { top: param(0).unwrap_or(1), left: param(1).unwrap_or(1), bottom: param(2), right: param(3) }
+    }
+}
+
+/// How bytes 0x80-0xFF in the input are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputEncoding {
+    /// `ESC % G`
+    #[default]
+    Utf8,
+    /// `ESC % @`: one byte per character, decoded with the parser's code page
+    /// (Latin-1 unless set). 0x80-0x9F are C1 controls when those are enabled.
+    SingleByte,
+}
+
+/// Urgency of a desktop notification (kitty's `u=`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+/// State of an `OSC 9 ; 4` progress report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressState {
+    /// `0`: remove the progress indicator
+    Hidden,
+    /// `1`
+    Normal,
+    /// `2`
+    Error,
+    /// `3`: busy, without a meaningful percentage
+    Indeterminate,
+    /// `4`
+    Paused,
+}
+
+/// How a string sequence (OSC, DCS) was terminated. Replies to queries use the same terminator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringTerminator {
+    /// BEL (0x07), the xterm extension
+    Bel,
+    /// ST (`ESC \`)
+    #[default]
+    St,
+}
+
+impl StringTerminator {
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            StringTerminator::Bel => b"\x07",
+            StringTerminator::St => b"\x1B\\",
+        }
+    }
+}
+
+/// 24-bit RGB colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CursorShape {
     #[default]
@@ -170,73 +601,55 @@ pub enum CursorShape {
     Beam, // Vertical bar
 }
 
+/// What a reset does with output held back by a synchronized update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncResetPolicy {
+    /// Emit the held output, followed by `EndSynchronizedUpdate`
+    #[default]
+    Flush,
+    /// Drop the held output, still emitting `EndSynchronizedUpdate`
+    Discard,
+}
+
+/// Part of the display or line an erase covers, relative to the cursor
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraseRange {
+    Forwards,
+    Backwards,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CharsetIndex {
+    #[default]
     G0,
     G1,
     G2,
     G3,
 }
 
-impl Default for CharsetIndex {
-    fn default() -> Self {
-        CharsetIndex::G0
-    }
-}
-
-/// Standard character sets
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Character sets that can be designated into G0-G3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum StandardCharset {
+    #[default]
     Ascii,
     SpecialCharacterAndLineDrawing,
+    /// British; a national replacement set, but available outside DECNRCM mode
+    Uk,
+    DecSupplemental,
+    DecTechnical,
+    /// The upper half of ISO 8859-1, a 96-character set
+    Latin1Supplemental,
+    // National replacement character sets, only designated in DECNRCM mode
+    Dutch,
+    Finnish,
+    French,
+    FrenchCanadian,
+    German,
+    Italian,
+    NorwegianDanish,
+    Portuguese,
+    Spanish,
+    Swedish,
+    Swiss,
 }
-
-impl Default for StandardCharset {
-    fn default() -> Self {
-        StandardCharset::Ascii
-    }
-}
-
-impl StandardCharset {
-    /// Map character according to charset
-    pub fn map(self, c: char) -> char {
-        match self {
-            StandardCharset::Ascii => c,
-            StandardCharset::SpecialCharacterAndLineDrawing => match c {
-                '_' => ' ',
-                '`' => '◆',
-                'a' => '▒',
-                'b' => '\u{2409}', // Symbol for horizontal tabulation
-                'c' => '\u{240c}', // Symbol for form feed
-                'd' => '\u{240d}', // Symbol for carriage return
-                'e' => '\u{240a}', // Symbol for line feed
-                'f' => '°',
-                'g' => '±',
-                'h' => '\u{2424}', // Symbol for newline
-                'i' => '\u{240b}', // Symbol for vertical tabulation
-                'j' => '┘',
-                'k' => '┐',
-                'l' => '┌',
-                'm' => '└',
-                'n' => '┼',
-                'o' => '⎺',
-                'p' => '⎻',
-                'q' => '─',
-                'r' => '⎼',
-                's' => '⎽',
-                't' => '├',
-                'u' => '┤',
-                'v' => '┴',
-                'w' => '┬',
-                'x' => '│',
-                'y' => '≤',
-                'z' => '≥',
-                '{' => 'π',
-                '|' => '≠',
-                '}' => '£',
-                '~' => '·',
-                _ => c,
-            },
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,564 @@
+use std::io::{self, Write};
+
+use crate::clipboard::base64_encode;
+use crate::osc::percent_encode;
+use crate::definitions::{
+    CharsetIndex, ControlEncoding, CursorShape, EraseRange, InputEncoding, Rect, Mode, ProgressState, Rgb, SelectGraphicRendition, Selection,
+    FullscreenMode, MaximizeMode, TerminalOutput, TitleTarget, Urgency, WindowOp,
+};
+
+impl SelectGraphicRendition {
+    /// SGR parameter string, e.g. `"1"` or `"38;2;255;0;0"`
+    pub fn params(&self) -> String {
+        use SelectGraphicRendition::*;
+        let code = match *self {
+            Reset => 0,
+            Bold => 1,
+            Faint => 2,
+            Italic => 3,
+            Underline => 4,
+            BlinkSlow => 5,
+            BlinkRapid => 6,
+            Reverse => 7,
+            Conceal => 8,
+            NormalIntensity => 22,
+            NotItalic => 23,
+            NotUnderline => 24,
+            Reveal => 28,
+            ForegroundBlack => 30,
+            ForegroundRed => 31,
+            ForegroundGreen => 32,
+            ForegroundYellow => 33,
+            ForegroundBlue => 34,
+            ForegroundMagenta => 35,
+            ForegroundCyan => 36,
+            ForegroundWhite => 37,
+            ForegroundDefault => 39,
+            BackgroundBlack => 40,
+            BackgroundRed => 41,
+            BackgroundGreen => 42,
+            BackgroundYellow => 43,
+            BackgroundBlue => 44,
+            BackgroundMagenta => 45,
+            BackgroundCyan => 46,
+            BackgroundWhite => 47,
+            BackgroundDefault => 49,
+            ForegroundBrightBlack => 90,
+            ForegroundBrightRed => 91,
+            ForegroundBrightGreen => 92,
+            ForegroundBrightYellow => 93,
+            ForegroundBrightBlue => 94,
+            ForegroundBrightMagenta => 95,
+            ForegroundBrightCyan => 96,
+            ForegroundBrightWhite => 97,
+            BackgroundBrightBlack => 100,
+            BackgroundBrightRed => 101,
+            BackgroundBrightGreen => 102,
+            BackgroundBrightYellow => 103,
+            BackgroundBrightBlue => 104,
+            BackgroundBrightMagenta => 105,
+            BackgroundBrightCyan => 106,
+            BackgroundBrightWhite => 107,
+            Foreground8Bit(i) => return format!("38;5;{}", i),
+            Background8Bit(i) => return format!("48;5;{}", i),
+            ForegroundTrueColor(r, g, b) => return format!("38;2;{};{};{}", r, g, b),
+            BackgroundTrueColor(r, g, b) => return format!("48;2;{};{};{}", r, g, b),
+            Unknown(code) => code,
+        };
+        code.to_string()
+    }
+
+    /// Write this rendition as a standalone `CSI ... m` sequence
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "\x1B[{}m", self.params())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Writing to a Vec never fails
+        let _ = self.write_to(&mut bytes);
+        bytes
+    }
+}
+
+impl ControlEncoding {
+    /// Rewrite a reply built with 7-bit controls into this encoding. `ESC Fe`
+    /// pairs (`ESC [`, `ESC ]`, `ESC P`, `ESC \`, ...) become single C1 bytes.
+    pub fn convert(self, reply: &[u8]) -> Vec<u8> {
+        if self == ControlEncoding::SevenBit {
+            return reply.to_vec();
+        }
+        let mut converted = Vec::with_capacity(reply.len());
+        let mut bytes = reply.iter().copied().peekable();
+        while let Some(byte) = bytes.next() {
+            match bytes.peek() {
+                Some(&fe @ 0x40..=0x5F) if byte == 0x1B => {
+                    converted.push(fe + 0x40);
+                    bytes.next();
+                }
+                _ => converted.push(byte),
+            }
+        }
+        converted
+    }
+}
+
+impl Mode {
+    /// Parameter used by SM/RM (ANSI) or DECSET/DECRST (`?`-prefixed) for this mode
+    pub fn params(&self) -> Vec<u8> {
+        match self {
+            Mode::Decckm => b"?1".to_vec(),
+            Mode::BracketedPaste => b"?2004".to_vec(),
+            Mode::ModifyOtherKeys => b"?1037".to_vec(),
+            Mode::Insert => b"4".to_vec(),
+            Mode::LineFeedNewLine => b"20".to_vec(),
+            Mode::Ansi => b"?2".to_vec(),
+            Mode::KeypadApplication => b"?66".to_vec(),
+            Mode::NationalReplacement => b"?42".to_vec(),
+            Mode::Columns132 => b"?3".to_vec(),
+            Mode::ReverseVideo => b"?5".to_vec(),
+            Mode::Origin => b"?6".to_vec(),
+            Mode::AutoWrap => b"?7".to_vec(),
+            Mode::ReverseWraparound => b"?45".to_vec(),
+            Mode::ExtendedReverseWraparound => b"?1045".to_vec(),
+            Mode::AltScreen => b"?47".to_vec(),
+            Mode::AltScreenClear => b"?1047".to_vec(),
+            Mode::SaveCursor => b"?1048".to_vec(),
+            Mode::BackarrowKey => b"?67".to_vec(),
+            Mode::LeftRightMargin => b"?69".to_vec(),
+            Mode::FocusReporting => b"?1004".to_vec(),
+            Mode::Unknown(params) => params.clone(),
+        }
+    }
+}
+
+impl TerminalOutput {
+    /// Write the canonical escape sequence for this output.
+    ///
+    /// Feeding the result to a fresh [`AnsiParser`](crate::AnsiParser) yields the
+    /// same value again, with these exceptions:
+    /// - `Data`, `SetTitle` and `SetIconName` are written verbatim, so they only
+    ///   round-trip when they contain no control characters.
+    /// - `WorkingDirectory` needs a path starting with `/`; an empty host reads
+    ///   back as `None`.
+    /// - `ResetMode(Mode::Ansi)` switches the parser to VT52 mode, where the
+    ///   output that follows reads differently.
+    /// - `SetLeftRightMargins` only reads back in DECLRMM mode.
+    /// - `FillRectangle` only reads back for the characters DECFRA accepts:
+    ///   32-126 and 160-255.
+    /// - National replacement sets in `ConfigureCharset` are only designated
+    ///   in DECNRCM mode.
+    /// - `Invalid`, `Extension`, `SetCursorPos { x: None, y: None }`, 96-character
+    ///   sets in G0, G0 in GR and single shifts of G0/G1 have no encoding and
+    ///   write nothing.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            TerminalOutput::Data(bytes) => w.write_all(bytes),
+            TerminalOutput::Osc { command, payload } => {
+                write!(w, "\x1B]{};", command)?;
+                w.write_all(payload)?;
+                w.write_all(b"\x1B\\")
+            }
+            TerminalOutput::NamedOsc { command, payload } => {
+                w.write_all(b"\x1B]")?;
+                w.write_all(command)?;
+                if !payload.is_empty() {
+                    w.write_all(b";")?;
+                    w.write_all(payload)?;
+                }
+                w.write_all(b"\x1B\\")
+            }
+            TerminalOutput::DeviceControlString { params, intermediates, data } => {
+                w.write_all(b"\x1BP")?;
+                write_params(w, params)?;
+                w.write_all(intermediates)?;
+                w.write_all(data)?;
+                w.write_all(b"\x1B\\")
+            }
+            TerminalOutput::SetCursorPos { x: Some(x), y: Some(y) } => write!(w, "\x1B[{};{}H", y, x),
+            TerminalOutput::SetCursorPos { x: Some(x), y: None } => write!(w, "\x1B[{}G", x),
+            TerminalOutput::SetCursorPos { x: None, y: Some(y) } => write!(w, "\x1B[{}d", y),
+            TerminalOutput::SetCursorPos { x: None, y: None } => Ok(()),
+            TerminalOutput::CursorUp(n) => write!(w, "\x1B[{}A", n),
+            TerminalOutput::CursorDown(n) => write!(w, "\x1B[{}B", n),
+            TerminalOutput::CursorForward(n) => write!(w, "\x1B[{}C", n),
+            TerminalOutput::CursorBackward(n) => write!(w, "\x1B[{}D", n),
+            TerminalOutput::ClearForwards => w.write_all(b"\x1B[J"),
+            TerminalOutput::ClearAll => w.write_all(b"\x1B[2J"),
+            TerminalOutput::ClearLineForwards => w.write_all(b"\x1B[K"),
+            TerminalOutput::Delete(n) => write!(w, "\x1B[{}P", n),
+            TerminalOutput::InsertSpaces(n) => write!(w, "\x1B[{}@", n),
+            TerminalOutput::Backspace => w.write_all(b"\x08"),
+            TerminalOutput::Newline => w.write_all(b"\n"),
+            TerminalOutput::VerticalTab => w.write_all(b"\x0B"),
+            TerminalOutput::HorizontalTab => w.write_all(b"\x09"),
+            TerminalOutput::Bell => w.write_all(b"\x07"),
+            TerminalOutput::Enquiry => w.write_all(b"\x05"),
+            TerminalOutput::FormFeed => w.write_all(b"\x0C"),
+            TerminalOutput::Index => w.write_all(b"\x1BD"),
+            TerminalOutput::ReverseIndex => w.write_all(b"\x1BM"),
+            TerminalOutput::NextLine => w.write_all(b"\x1BE"),
+            TerminalOutput::CarriageReturn => w.write_all(b"\r"),
+            TerminalOutput::Sgr(sgr) => sgr.write_to(w),
+            TerminalOutput::SetCursorVisibility(true) => w.write_all(b"\x1B[?25h"),
+            TerminalOutput::SetCursorVisibility(false) => w.write_all(b"\x1B[?25l"),
+            TerminalOutput::SetMode(mode) => {
+                w.write_all(b"\x1B[")?;
+                w.write_all(&mode.params())?;
+                w.write_all(b"h")
+            }
+            TerminalOutput::ResetMode(mode) => {
+                w.write_all(b"\x1B[")?;
+                w.write_all(&mode.params())?;
+                w.write_all(b"l")
+            }
+            TerminalOutput::EnterAltScreen => w.write_all(b"\x1B[?1049h"),
+            TerminalOutput::ExitAltScreen => w.write_all(b"\x1B[?1049l"),
+            TerminalOutput::Invalid | TerminalOutput::Extension(_) => Ok(()),
+            TerminalOutput::WorkingDirectory { host, path } => {
+                // Percent-encoding the host too keeps a stray `/` from ending it early
+                let host = host.as_deref().map(|host| percent_encode(host.as_bytes())).unwrap_or_default();
+                write!(w, "\x1B]7;file://{}{}\x1B\\", host.replace('/', "%2F"), percent_encode(path))
+            }
+            TerminalOutput::Notification { title, body, id, urgency } => {
+                write_notification(w, title.as_deref(), body, id.as_deref(), *urgency)
+            }
+            TerminalOutput::Progress { state, percent } => {
+                let state = match state {
+                    ProgressState::Hidden => 0,
+                    ProgressState::Normal => 1,
+                    ProgressState::Error => 2,
+                    ProgressState::Indeterminate => 3,
+                    ProgressState::Paused => 4,
+                };
+                match percent {
+                    Some(percent) => write!(w, "\x1B]9;4;{};{}\x1B\\", state, percent),
+                    None => write!(w, "\x1B]9;4;{}\x1B\\", state),
+                }
+            }
+            TerminalOutput::SetTitle(title) => write!(w, "\x1B]2;{}\x1B\\", title),
+            TerminalOutput::SetIconName(name) => write!(w, "\x1B]1;{}\x1B\\", name),
+            TerminalOutput::PushTitle(target) => write_title_stack_op(w, 22, *target),
+            TerminalOutput::PopTitle(target) => write_title_stack_op(w, 23, *target),
+            TerminalOutput::WindowOp(op) => write_window_op(w, op),
+            TerminalOutput::SetControlEncoding(ControlEncoding::SevenBit) => w.write_all(b"\x1B F"),
+            TerminalOutput::SetControlEncoding(ControlEncoding::EightBit) => w.write_all(b"\x1B G"),
+            TerminalOutput::SetInputEncoding(InputEncoding::Utf8) => w.write_all(b"\x1B%G"),
+            TerminalOutput::SetInputEncoding(InputEncoding::SingleByte) => w.write_all(b"\x1B%@"),
+            TerminalOutput::PromptStart => w.write_all(b"\x1B]133;A\x1B\\"),
+            TerminalOutput::CommandStart => w.write_all(b"\x1B]133;B\x1B\\"),
+            TerminalOutput::CommandExecuted => w.write_all(b"\x1B]133;C\x1B\\"),
+            TerminalOutput::CommandFinished { exit_code: Some(code) } => write!(w, "\x1B]133;D;{}\x1B\\", code),
+            TerminalOutput::CommandFinished { exit_code: None } => w.write_all(b"\x1B]133;D\x1B\\"),
+            TerminalOutput::DeviceControl { code } => w.write_all(&[*code]),
+            TerminalOutput::SetScrollingRegion { top, bottom } => {
+                w.write_all(b"\x1B[")?;
+                write_margins(w, *top, *bottom)?;
+                w.write_all(b"r")
+            }
+            TerminalOutput::SetLeftRightMargins { left, right } => {
+                w.write_all(b"\x1B[")?;
+                write_margins(w, *left, *right)?;
+                w.write_all(b"s")
+            }
+            TerminalOutput::CopyRectangle { source, top, left } => {
+                w.write_all(b"\x1B[")?;
+                write_rect(w, source)?;
+                write!(w, ";1;{};{};1$v", top, left)
+            }
+            TerminalOutput::FillRectangle { ch, area } => {
+                write!(w, "\x1B[{};", u32::from(*ch))?;
+                write_rect(w, area)?;
+                w.write_all(b"$x")
+            }
+            TerminalOutput::EraseRectangle(area) => {
+                w.write_all(b"\x1B[")?;
+                write_rect(w, area)?;
+                w.write_all(b"$z")
+            }
+            TerminalOutput::SelectiveEraseRectangle(area) => {
+                w.write_all(b"\x1B[")?;
+                write_rect(w, area)?;
+                w.write_all(b"${")
+            }
+            TerminalOutput::ChangeRectangleAttributes { area, attributes } => {
+                w.write_all(b"\x1B[")?;
+                write_rect(w, area)?;
+                for attribute in attributes {
+                    write!(w, ";{}", attribute.params())?;
+                }
+                w.write_all(b"$r")
+            }
+            TerminalOutput::ReverseRectangleAttributes { area, attributes } => {
+                w.write_all(b"\x1B[")?;
+                write_rect(w, area)?;
+                for attribute in attributes {
+                    write!(w, ";{}", attribute.params())?;
+                }
+                w.write_all(b"$t")
+            }
+            TerminalOutput::RequestChecksum { id, page, area } => {
+                write!(w, "\x1B[{};{};", id, page)?;
+                write_rect(w, area)?;
+                w.write_all(b"*y")
+            }
+            TerminalOutput::SetCharacterProtection(protected) => {
+                w.write_all(if *protected { b"\x1B[1\"q" } else { b"\x1B[0\"q" })
+            }
+            TerminalOutput::StartGuardedArea => w.write_all(b"\x1BV"),
+            TerminalOutput::EndGuardedArea => w.write_all(b"\x1BW"),
+            TerminalOutput::SelectiveEraseDisplay(range) => write!(w, "\x1B[?{}J", erase_range_param(*range)),
+            TerminalOutput::SelectiveEraseLine(range) => write!(w, "\x1B[?{}K", erase_range_param(*range)),
+            TerminalOutput::FullReset => w.write_all(b"\x1Bc"),
+            TerminalOutput::SoftReset => w.write_all(b"\x1B[!p"),
+            TerminalOutput::SaveCursor => w.write_all(b"\x1B7"),
+            TerminalOutput::RestoreCursor => w.write_all(b"\x1B8"),
+            TerminalOutput::BeginSynchronizedUpdate => w.write_all(b"\x1B[?2026h"),
+            TerminalOutput::EndSynchronizedUpdate => w.write_all(b"\x1B[?2026l"),
+            TerminalOutput::SetCursorStyle { shape, blinking } => {
+                let ps = match shape {
+                    CursorShape::Block => 1,
+                    CursorShape::Underline => 3,
+                    CursorShape::Beam => 5,
+                } + if *blinking { 0 } else { 1 };
+                write!(w, "\x1B[{} q", ps)
+            }
+            TerminalOutput::SetHyperlink { id, uri } => match id {
+                Some(id) => write!(w, "\x1B]8;id={};{}\x1B\\", id, uri),
+                None => write!(w, "\x1B]8;;{}\x1B\\", uri),
+            },
+            TerminalOutput::ClearHyperlink => w.write_all(b"\x1B]8;;\x1B\\"),
+            TerminalOutput::SetActiveCharset(index) => match index {
+                CharsetIndex::G0 => w.write_all(b"\x0F"),
+                CharsetIndex::G1 => w.write_all(b"\x0E"),
+                CharsetIndex::G2 => w.write_all(b"\x1Bn"),
+                CharsetIndex::G3 => w.write_all(b"\x1Bo"),
+            },
+            TerminalOutput::ConfigureCharset { index, charset } => {
+                let intermediate = match (index, charset.is_96_character()) {
+                    (CharsetIndex::G0, false) => b'(',
+                    (CharsetIndex::G1, false) => b')',
+                    (CharsetIndex::G2, false) => b'*',
+                    (CharsetIndex::G3, false) => b'+',
+                    (CharsetIndex::G0, true) => return Ok(()),
+                    (CharsetIndex::G1, true) => b'-',
+                    (CharsetIndex::G2, true) => b'.',
+                    (CharsetIndex::G3, true) => b'/',
+                };
+                w.write_all(&[0x1B, intermediate])?;
+                w.write_all(charset.designator())
+            }
+            TerminalOutput::SetActiveGrCharset(index) => match index {
+                CharsetIndex::G0 => Ok(()),
+                CharsetIndex::G1 => w.write_all(b"\x1B~"),
+                CharsetIndex::G2 => w.write_all(b"\x1B}"),
+                CharsetIndex::G3 => w.write_all(b"\x1B|"),
+            },
+            TerminalOutput::SingleShift(index) => match index {
+                CharsetIndex::G2 => w.write_all(b"\x1BN"),
+                CharsetIndex::G3 => w.write_all(b"\x1BO"),
+                CharsetIndex::G0 | CharsetIndex::G1 => Ok(()),
+            },
+            TerminalOutput::SetDoubleHeightLine { top_half: true } => w.write_all(b"\x1B#3"),
+            TerminalOutput::SetDoubleHeightLine { top_half: false } => w.write_all(b"\x1B#4"),
+            TerminalOutput::SetDoubleWidthLine => w.write_all(b"\x1B#6"),
+            TerminalOutput::SetSingleWidthLine => w.write_all(b"\x1B#5"),
+            TerminalOutput::ScreenAlignmentTest => w.write_all(b"\x1B#8"),
+            TerminalOutput::SetPaletteColor { index, color } => {
+                write!(w, "\x1B]4;{};{}\x1B\\", index, short_spec(*color))
+            }
+            TerminalOutput::QueryPaletteColor { index, terminator } => {
+                write!(w, "\x1B]4;{};?", index)?;
+                w.write_all(terminator.as_bytes())
+            }
+            TerminalOutput::ResetPaletteColor(Some(index)) => write!(w, "\x1B]104;{}\x1B\\", index),
+            TerminalOutput::ResetPaletteColor(None) => w.write_all(b"\x1B]104\x1B\\"),
+            TerminalOutput::SetDynamicColor { kind, color } => {
+                write!(w, "\x1B]{};{}\x1B\\", kind.osc_number(), short_spec(*color))
+            }
+            TerminalOutput::QueryDynamicColor { kind, terminator } => {
+                write!(w, "\x1B]{};?", kind.osc_number())?;
+                w.write_all(terminator.as_bytes())
+            }
+            TerminalOutput::ResetDynamicColor(kind) => write!(w, "\x1B]{}\x1B\\", kind.osc_number() + 100),
+            TerminalOutput::ClipboardSet { selection, data } => {
+                write_selection(w, selection)?;
+                w.write_all(base64_encode(data).as_bytes())?;
+                w.write_all(b"\x1B\\")
+            }
+            TerminalOutput::ClipboardQuery { selection, terminator } => {
+                write_selection(w, selection)?;
+                w.write_all(b"?")?;
+                w.write_all(terminator.as_bytes())
+            }
+            TerminalOutput::ClipboardClear { selection } => {
+                write_selection(w, selection)?;
+                w.write_all(b"\x1B\\")
+            }
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Writing to a Vec never fails
+        let _ = self.write_to(&mut bytes);
+        bytes
+    }
+}
+
+/// `rgb:rr/gg/bb`, which parses back to exactly the same 8-bit colour
+fn short_spec(color: Rgb) -> String {
+    format!("rgb:{:02x}/{:02x}/{:02x}", color.r, color.g, color.b)
+}
+
+fn write_window_op<W: Write>(w: &mut W, op: &WindowOp) -> io::Result<()> {
+    let size = |size: Option<usize>| size.map(|size| size.to_string()).unwrap_or_default();
+    match *op {
+        WindowOp::Deiconify => w.write_all(b"\x1B[1t"),
+        WindowOp::Iconify => w.write_all(b"\x1B[2t"),
+        WindowOp::Move { x, y } => write!(w, "\x1B[3;{};{}t", x, y),
+        WindowOp::ResizePixels { width, height } => write!(w, "\x1B[4;{};{}t", size(height), size(width)),
+        WindowOp::Raise => w.write_all(b"\x1B[5t"),
+        WindowOp::Lower => w.write_all(b"\x1B[6t"),
+        WindowOp::Refresh => w.write_all(b"\x1B[7t"),
+        WindowOp::ResizeChars { columns, rows } => write!(w, "\x1B[8;{};{}t", size(rows), size(columns)),
+        WindowOp::Maximize(mode) => {
+            let ps = match mode {
+                MaximizeMode::Restore => 0,
+                MaximizeMode::Both => 1,
+                MaximizeMode::Vertically => 2,
+                MaximizeMode::Horizontally => 3,
+            };
+            write!(w, "\x1B[9;{}t", ps)
+        }
+        WindowOp::Fullscreen(mode) => {
+            let ps = match mode {
+                FullscreenMode::Exit => 0,
+                FullscreenMode::Enter => 1,
+                FullscreenMode::Toggle => 2,
+            };
+            write!(w, "\x1B[10;{}t", ps)
+        }
+        WindowOp::ReportState => w.write_all(b"\x1B[11t"),
+        WindowOp::ReportPosition { text_area: false } => w.write_all(b"\x1B[13t"),
+        WindowOp::ReportPosition { text_area: true } => w.write_all(b"\x1B[13;2t"),
+        WindowOp::ReportSizePixels { text_area: true } => w.write_all(b"\x1B[14t"),
+        WindowOp::ReportSizePixels { text_area: false } => w.write_all(b"\x1B[14;2t"),
+        WindowOp::ReportScreenSizePixels => w.write_all(b"\x1B[15t"),
+        WindowOp::ReportCellSizePixels => w.write_all(b"\x1B[16t"),
+        WindowOp::ReportSizeChars => w.write_all(b"\x1B[18t"),
+        WindowOp::ReportScreenSizeChars => w.write_all(b"\x1B[19t"),
+        WindowOp::ReportIconName => w.write_all(b"\x1B[20t"),
+        WindowOp::ReportTitle => w.write_all(b"\x1B[21t"),
+        WindowOp::ResizeLines(lines) => write!(w, "\x1B[{}t", lines),
+    }
+}
+
+fn write_title_stack_op<W: Write>(w: &mut W, op: usize, target: TitleTarget) -> io::Result<()> {
+    match target {
+        TitleTarget::Both => write!(w, "\x1B[{}t", op),
+        TitleTarget::IconName => write!(w, "\x1B[{};1t", op),
+        TitleTarget::Title => write!(w, "\x1B[{};2t", op),
+    }
+}
+
+/// Notifications are always written as kitty's OSC 99, the only form that
+/// carries every field. A title and body go out as two chunks.
+fn write_notification<W: Write>(
+    w: &mut W,
+    title: Option<&str>,
+    body: &str,
+    id: Option<&str>,
+    urgency: Urgency,
+) -> io::Result<()> {
+    let mut metadata = Vec::new();
+    if let Some(id) = id {
+        metadata.push(format!("i={}", id));
+    }
+    match urgency {
+        Urgency::Low => metadata.push("u=0".to_string()),
+        Urgency::Normal => {}
+        Urgency::Critical => metadata.push("u=2".to_string()),
+    }
+    match title {
+        Some(title) if body.is_empty() => write_notification_chunk(w, metadata, title),
+        Some(title) => {
+            let mut first = metadata.clone();
+            first.push("d=0".to_string());
+            write_notification_chunk(w, first, title)?;
+            metadata.push("p=body".to_string());
+            write_notification_chunk(w, metadata, body)
+        }
+        None => {
+            metadata.push("p=body".to_string());
+            write_notification_chunk(w, metadata, body)
+        }
+    }
+}
+
+/// Text with control characters is base64 encoded (`e=1`)
+fn write_notification_chunk<W: Write>(w: &mut W, mut metadata: Vec<String>, text: &str) -> io::Result<()> {
+    let plain = !text.bytes().any(|b| b.is_ascii_control());
+    if !plain {
+        metadata.push("e=1".to_string());
+    }
+    write!(w, "\x1B]99;{};", metadata.join(":"))?;
+    if plain {
+        w.write_all(text.as_bytes())?;
+    } else {
+        w.write_all(base64_encode(text.as_bytes()).as_bytes())?;
+    }
+    w.write_all(b"\x1B\\")
+}
+
+/// `OSC 52 ; Pc ;`, leaving the data to the caller
+fn write_selection<W: Write>(w: &mut W, selection: &[Selection]) -> io::Result<()> {
+    w.write_all(b"\x1B]52;")?;
+    for target in selection {
+        w.write_all(&[target.as_byte()])?;
+    }
+    w.write_all(b";")
+}
+
+fn write_params<W: Write>(w: &mut W, params: &[usize]) -> io::Result<()> {
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            w.write_all(b";")?;
+        }
+        write!(w, "{}", param)?;
+    }
+    Ok(())
+}
+
+/// `Pt ; Pb` or `Pl ; Pr`, leaving defaults empty
+fn write_margins<W: Write>(w: &mut W, first: Option<usize>, second: Option<usize>) -> io::Result<()> {
+    if let Some(first) = first {
+        write!(w, "{}", first)?;
+    }
+    if let Some(second) = second {
+        write!(w, ";{}", second)?;
+    }
+    Ok(())
+}
+
+/// Ps for DECSED/DECSEL
+fn erase_range_param(range: EraseRange) -> u8 {
+    match range {
+        EraseRange::Forwards => 0,
+        EraseRange::Backwards => 1,
+        EraseRange::All => 2,
+    }
+}
+
+/// `Pt ; Pl ; Pb ; Pr`, leaving a default bottom/right empty
+fn write_rect<W: Write>(w: &mut W, area: &Rect) -> io::Result<()> {
+    write!(w, "{};{};", area.top, area.left)?;
+    if let Some(bottom) = area.bottom {
+        write!(w, "{}", bottom)?;
+    }
+    w.write_all(b";")?;
+    if let Some(right) = area.right {
+        write!(w, "{}", right)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,629 @@
+use std::fmt::Write as _;
+
+use crate::definitions::{FormatTag, Rgb, SelectGraphicRendition, TerminalOutput};
+use crate::parser::AnsiParser;
+
+/// Colours used when rendering exported output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    /// The 8 standard colours followed by their 8 bright variants
+    pub ansi: [Rgb; 16],
+    pub foreground: Rgb,
+    pub background: Rgb,
+}
+
+impl Default for Palette {
+    /// xterm's default colours
+    fn default() -> Self {
+        Palette {
+            ansi: [
+                Rgb::new(0x00, 0x00, 0x00),
+                Rgb::new(0xcd, 0x00, 0x00),
+                Rgb::new(0x00, 0xcd, 0x00),
+                Rgb::new(0xcd, 0xcd, 0x00),
+                Rgb::new(0x00, 0x00, 0xee),
+                Rgb::new(0xcd, 0x00, 0xcd),
+                Rgb::new(0x00, 0xcd, 0xcd),
+                Rgb::new(0xe5, 0xe5, 0xe5),
+                Rgb::new(0x7f, 0x7f, 0x7f),
+                Rgb::new(0xff, 0x00, 0x00),
+                Rgb::new(0x00, 0xff, 0x00),
+                Rgb::new(0xff, 0xff, 0x00),
+                Rgb::new(0x5c, 0x5c, 0xff),
+                Rgb::new(0xff, 0x00, 0xff),
+                Rgb::new(0x00, 0xff, 0xff),
+                Rgb::new(0xff, 0xff, 0xff),
+            ],
+            foreground: Rgb::new(0xe5, 0xe5, 0xe5),
+            background: Rgb::new(0x00, 0x00, 0x00),
+        }
+    }
+}
+
+impl Palette {
+    /// Resolve an entry of the 256-colour palette
+    pub fn indexed(&self, index: u8) -> Rgb {
+        match index {
+            0..=15 => self.ansi[index as usize],
+            16..=231 => {
+                let i = index - 16;
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                Rgb::new(level(i / 36), level((i / 6) % 6), level(i % 6))
+            }
+            _ => {
+                let v = 8 + (index - 232) * 10;
+                Rgb::new(v, v, v)
+            }
+        }
+    }
+
+    /// Resolve a colour carried by an SGR (either foreground or background variant)
+    fn resolve(&self, sgr: SelectGraphicRendition) -> Rgb {
+        use SelectGraphicRendition::*;
+        match sgr {
+            ForegroundDefault => self.foreground,
+            BackgroundDefault => self.background,
+            Foreground8Bit(i) | Background8Bit(i) => self.indexed(i),
+            ForegroundTrueColor(r, g, b) | BackgroundTrueColor(r, g, b) => Rgb::new(r, g, b),
+            other => ansi_index(other).map(|i| self.ansi[i as usize]).unwrap_or(self.foreground),
+        }
+    }
+}
+
+/// Index into the 16 colour table for basic and bright colour SGRs
+fn ansi_index(sgr: SelectGraphicRendition) -> Option<u8> {
+    use SelectGraphicRendition::*;
+    let index = match sgr {
+        ForegroundBlack | BackgroundBlack => 0,
+        ForegroundRed | BackgroundRed => 1,
+        ForegroundGreen | BackgroundGreen => 2,
+        ForegroundYellow | BackgroundYellow => 3,
+        ForegroundBlue | BackgroundBlue => 4,
+        ForegroundMagenta | BackgroundMagenta => 5,
+        ForegroundCyan | BackgroundCyan => 6,
+        ForegroundWhite | BackgroundWhite => 7,
+        ForegroundBrightBlack | BackgroundBrightBlack => 8,
+        ForegroundBrightRed | BackgroundBrightRed => 9,
+        ForegroundBrightGreen | BackgroundBrightGreen => 10,
+        ForegroundBrightYellow | BackgroundBrightYellow => 11,
+        ForegroundBrightBlue | BackgroundBrightBlue => 12,
+        ForegroundBrightMagenta | BackgroundBrightMagenta => 13,
+        ForegroundBrightCyan | BackgroundBrightCyan => 14,
+        ForegroundBrightWhite | BackgroundBrightWhite => 15,
+        Foreground8Bit(i) | Background8Bit(i) if i < 16 => i,
+        _ => return None,
+    };
+    Some(index)
+}
+
+pub(crate) fn is_foreground(sgr: SelectGraphicRendition) -> bool {
+    use SelectGraphicRendition::*;
+    matches!(
+        sgr,
+        ForegroundDefault | ForegroundBlack | ForegroundRed | ForegroundGreen | ForegroundYellow
+            | ForegroundBlue | ForegroundMagenta | ForegroundCyan | ForegroundWhite
+            | ForegroundBrightBlack | ForegroundBrightRed | ForegroundBrightGreen
+            | ForegroundBrightYellow | ForegroundBrightBlue | ForegroundBrightMagenta
+            | ForegroundBrightCyan | ForegroundBrightWhite | Foreground8Bit(_)
+            | ForegroundTrueColor(..)
+    )
+}
+
+pub(crate) fn is_background(sgr: SelectGraphicRendition) -> bool {
+    use SelectGraphicRendition::*;
+    matches!(
+        sgr,
+        BackgroundDefault | BackgroundBlack | BackgroundRed | BackgroundGreen | BackgroundYellow
+            | BackgroundBlue | BackgroundMagenta | BackgroundCyan | BackgroundWhite
+            | BackgroundBrightBlack | BackgroundBrightRed | BackgroundBrightGreen
+            | BackgroundBrightYellow | BackgroundBrightBlue | BackgroundBrightMagenta
+            | BackgroundBrightCyan | BackgroundBrightWhite | Background8Bit(_)
+            | BackgroundTrueColor(..)
+    )
+}
+
+/// Pen state while replaying output onto the canvas
+#[derive(Clone, Debug, PartialEq)]
+struct Pen {
+    fg: SelectGraphicRendition,
+    bg: SelectGraphicRendition,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    blink: bool,
+    reverse: bool,
+    url: Option<String>,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Pen {
+            fg: SelectGraphicRendition::ForegroundDefault,
+            bg: SelectGraphicRendition::BackgroundDefault,
+            bold: false,
+            italic: false,
+            underline: false,
+            blink: false,
+            reverse: false,
+            url: None,
+        }
+    }
+}
+
+impl Pen {
+    fn apply(&mut self, sgr: SelectGraphicRendition) {
+        use SelectGraphicRendition::*;
+        match sgr {
+            // Hyperlinks are not part of SGR state and survive a reset
+            Reset => {
+                let url = self.url.take();
+                *self = Pen { url, ..Pen::default() };
+            }
+            Bold => self.bold = true,
+            NormalIntensity => self.bold = false,
+            Italic => self.italic = true,
+            NotItalic => self.italic = false,
+            Underline => self.underline = true,
+            NotUnderline => self.underline = false,
+            BlinkSlow | BlinkRapid => self.blink = true,
+            Unknown(25) => self.blink = false,
+            Reverse => self.reverse = true,
+            Unknown(27) => self.reverse = false,
+            sgr if is_foreground(sgr) => self.fg = sgr,
+            sgr if is_background(sgr) => self.bg = sgr,
+            _ => {}
+        }
+    }
+
+    /// Produce the tag for a span drawn with this pen, or `None` for unstyled text
+    fn tag(&self, start: usize, end: usize) -> Option<FormatTag> {
+        if *self == Pen::default() {
+            return None;
+        }
+        let (fg_color, bg_color) = if self.reverse { (self.bg, self.fg) } else { (self.fg, self.bg) };
+        Some(FormatTag {
+            start,
+            end,
+            blink: self.blink,
+            fg_color,
+            bg_color,
+            bold: self.bold,
+            italic: self.italic,
+            underline: self.underline,
+            url: self.url.clone(),
+        })
+    }
+}
+
+/// Furthest column cursor movement can reach, so untrusted input can't make
+/// the canvas allocate huge blank lines. Printed text may go beyond it.
+const MAX_CURSOR_COLUMN: usize = 4096;
+
+/// URI schemes exported as links, matching the default sanitizer policy
+const LINK_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// Minimal line-oriented canvas. Only relative horizontal movement is modelled,
+/// which covers line-based CLI output such as progress bars and prompts.
+#[derive(Default)]
+struct Canvas {
+    lines: Vec<Vec<(char, Pen)>>,
+    row: usize,
+    col: usize,
+    pen: Pen,
+}
+
+impl Canvas {
+    fn apply(&mut self, item: &TerminalOutput) {
+        if self.lines.is_empty() {
+            self.lines.push(Vec::new());
+        }
+        match item {
+            TerminalOutput::Data(bytes) => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    self.put(c);
+                }
+            }
+            TerminalOutput::Newline
+            | TerminalOutput::VerticalTab
+            | TerminalOutput::FormFeed
+            | TerminalOutput::NextLine => {
+                self.line_feed();
+                self.col = 0;
+            }
+            TerminalOutput::Index => self.line_feed(),
+            TerminalOutput::CarriageReturn => self.col = 0,
+            TerminalOutput::Backspace => self.col = self.col.saturating_sub(1),
+            // Tab stops every 8 columns
+            TerminalOutput::HorizontalTab => self.col = (self.col / 8 + 1) * 8,
+            TerminalOutput::CursorForward(n) => {
+                self.col = self.col.saturating_add(*n).min(MAX_CURSOR_COLUMN).max(self.col)
+            }
+            TerminalOutput::CursorBackward(n) => self.col = self.col.saturating_sub(*n),
+            TerminalOutput::ClearLineForwards => self.lines[self.row].truncate(self.col),
+            TerminalOutput::Sgr(sgr) => self.pen.apply(*sgr),
+            // Links with other schemes (e.g. `javascript:`) are rendered as plain text
+            TerminalOutput::SetHyperlink { uri, .. } => {
+                self.pen.url = LINK_SCHEMES
+                    .iter()
+                    .any(|scheme| uri.split_once(':').is_some_and(|(s, _)| s.eq_ignore_ascii_case(scheme)))
+                    .then(|| uri.clone())
+            }
+            TerminalOutput::ClearHyperlink => self.pen.url = None,
+            _ => {}
+        }
+    }
+
+    fn line_feed(&mut self) {
+        self.row += 1;
+        if self.row == self.lines.len() {
+            self.lines.push(Vec::new());
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        let line = &mut self.lines[self.row];
+        if line.len() < self.col {
+            line.resize(self.col, (' ', Pen::default()));
+        }
+        if self.col < line.len() {
+            line[self.col] = (c, self.pen.clone());
+        } else {
+            line.push((c, self.pen.clone()));
+        }
+        self.col += 1;
+    }
+
+    fn into_styled_text(self) -> StyledText {
+        let mut text = String::new();
+        let mut tags: Vec<FormatTag> = Vec::new();
+        for (row, line) in self.lines.iter().enumerate() {
+            if row > 0 {
+                text.push('\n');
+            }
+            let mut run: Option<(usize, &Pen)> = None;
+            for (c, pen) in line {
+                if run.is_some_and(|(_, current)| current != pen) {
+                    let (start, current) = run.take().unwrap();
+                    tags.extend(current.tag(start, text.len()));
+                }
+                if run.is_none() {
+                    run = Some((text.len(), pen));
+                }
+                text.push(*c);
+            }
+            if let Some((start, current)) = run {
+                tags.extend(current.tag(start, text.len()));
+            }
+        }
+        StyledText { text, tags }
+    }
+}
+
+/// Plain text together with the styled spans that cover it. Tags never cross
+/// line boundaries and are sorted by `start`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StyledText {
+    pub text: String,
+    pub tags: Vec<FormatTag>,
+}
+
+/// How colours and attributes are attached to HTML spans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StyleMode {
+    /// `style="..."` attributes on every span
+    #[default]
+    Inline,
+    /// Class names plus an embedded `<style>` sheet generated from the palette.
+    /// Colours outside the 16 colour table fall back to inline styles.
+    Classes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlOptions {
+    pub palette: Palette,
+    pub style_mode: StyleMode,
+    /// Prefix for generated class names
+    pub class_prefix: String,
+    /// Wrap the fragment in `<!DOCTYPE html>` / `<html>` / `<body>`
+    pub full_document: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            palette: Palette::default(),
+            style_mode: StyleMode::Inline,
+            class_prefix: "term".to_string(),
+            full_document: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    pub palette: Palette,
+    pub font_family: String,
+    pub font_size: u32,
+    /// Width of a single cell in pixels
+    pub cell_width: u32,
+    /// Height of a single row in pixels
+    pub line_height: u32,
+    /// Blank space around the grid in pixels
+    pub padding: u32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            palette: Palette::default(),
+            font_family: "monospace".to_string(),
+            font_size: 14,
+            cell_width: 9,
+            line_height: 18,
+            padding: 8,
+        }
+    }
+}
+
+impl StyledText {
+    /// Parse a raw byte stream and collect its text and styling
+    pub fn from_bytes(bytes: &[u8]) -> StyledText {
+        let mut parser = AnsiParser::new();
+        StyledText::from_outputs(&parser.push(bytes))
+    }
+
+    /// Replay parser output. Cursor addressing, screen clears and other
+    /// full-screen operations are ignored.
+    pub fn from_outputs(outputs: &[TerminalOutput]) -> StyledText {
+        let mut canvas = Canvas::default();
+        for item in outputs {
+            canvas.apply(item);
+        }
+        canvas.into_styled_text()
+    }
+
+    /// Split the text into consecutive segments, each with the tag covering it (if any)
+    fn segments(&self) -> Vec<(&str, Option<&FormatTag>)> {
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        for tag in &self.tags {
+            if tag.start > pos {
+                segments.push((&self.text[pos..tag.start], None));
+            }
+            segments.push((&self.text[tag.start..tag.end], Some(tag)));
+            pos = tag.end;
+        }
+        if pos < self.text.len() {
+            segments.push((&self.text[pos..], None));
+        }
+        segments
+    }
+
+    /// Render as an HTML `<pre>` block (or a full document, see [`HtmlOptions`])
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let palette = &options.palette;
+        let prefix = &options.class_prefix;
+        let mut html = String::new();
+
+        if options.full_document {
+            html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body>\n");
+        }
+
+        match options.style_mode {
+            StyleMode::Inline => {
+                let _ = write!(
+                    html,
+                    "<pre style=\"color:{};background-color:{}\">",
+                    hex(palette.foreground),
+                    hex(palette.background)
+                );
+            }
+            StyleMode::Classes => {
+                html.push_str("<style>\n");
+                let _ = writeln!(
+                    html,
+                    ".{prefix}{{color:{};background-color:{}}}",
+                    hex(palette.foreground),
+                    hex(palette.background)
+                );
+                for (i, color) in palette.ansi.iter().enumerate() {
+                    let _ = writeln!(html, ".{prefix}-fg-{i}{{color:{}}}", hex(*color));
+                }
+                for (i, color) in palette.ansi.iter().enumerate() {
+                    let _ = writeln!(html, ".{prefix}-bg-{i}{{background-color:{}}}", hex(*color));
+                }
+                let _ = writeln!(html, ".{prefix}-bold{{font-weight:bold}}");
+                let _ = writeln!(html, ".{prefix}-italic{{font-style:italic}}");
+                let _ = writeln!(html, ".{prefix}-underline{{text-decoration:underline}}");
+                let _ = writeln!(html, ".{prefix}-blink{{text-decoration:blink}}");
+                let _ = writeln!(html, ".{prefix}-underline.{prefix}-blink{{text-decoration:underline blink}}");
+                html.push_str("</style>\n");
+                let _ = write!(html, "<pre class=\"{}\">", escape(prefix));
+            }
+        }
+
+        for (text, tag) in self.segments() {
+            let Some(tag) = tag else {
+                html.push_str(&escape(text));
+                continue;
+            };
+            if let Some(url) = &tag.url {
+                let _ = write!(html, "<a href=\"{}\">", escape(url));
+            }
+            let (classes, styles) = html_attributes(tag, options);
+            if classes.is_empty() && styles.is_empty() {
+                html.push_str(&escape(text));
+            } else {
+                html.push_str("<span");
+                if !classes.is_empty() {
+                    let _ = write!(html, " class=\"{}\"", classes.join(" "));
+                }
+                if !styles.is_empty() {
+                    let _ = write!(html, " style=\"{}\"", styles.join(";"));
+                }
+                let _ = write!(html, ">{}</span>", escape(text));
+            }
+            if tag.url.is_some() {
+                html.push_str("</a>");
+            }
+        }
+        html.push_str("</pre>");
+
+        if options.full_document {
+            html.push_str("\n</body>\n</html>\n");
+        }
+        html
+    }
+
+    /// Render as an SVG image laid out on a fixed monospace cell grid
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let palette = &options.palette;
+        let lines: Vec<&str> = self.text.split('\n').collect();
+        let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u32;
+        let width = options.padding * 2 + columns * options.cell_width;
+        let height = options.padding * 2 + lines.len() as u32 * options.line_height;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        );
+        let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(palette.background));
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"{}\" font-size=\"{}\" fill=\"{}\">",
+            escape(&options.font_family),
+            options.font_size,
+            hex(palette.foreground)
+        );
+
+        let (mut row, mut col) = (0u32, 0u32);
+        for (segment, tag) in self.segments() {
+            for (i, text) in segment.split('\n').enumerate() {
+                if i > 0 {
+                    row += 1;
+                    col = 0;
+                }
+                let cells = text.chars().count() as u32;
+                if cells == 0 {
+                    continue;
+                }
+                let x = options.padding + col * options.cell_width;
+                let y = options.padding + row * options.line_height;
+                col += cells;
+
+                if let Some(url) = tag.and_then(|tag| tag.url.as_ref()) {
+                    let _ = write!(svg, "<a href=\"{}\">", escape(url));
+                }
+                if let Some(tag) = tag.filter(|tag| tag.bg_color != SelectGraphicRendition::BackgroundDefault) {
+                    let _ = write!(
+                        svg,
+                        "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        cells * options.cell_width,
+                        options.line_height,
+                        hex(palette.resolve(tag.bg_color))
+                    );
+                }
+                if !text.trim_matches(' ').is_empty() {
+                    let _ = write!(
+                        svg,
+                        "<text x=\"{x}\" y=\"{}\" xml:space=\"preserve\"",
+                        y + options.font_size
+                    );
+                    if let Some(tag) = tag {
+                        if tag.fg_color != SelectGraphicRendition::ForegroundDefault {
+                            let _ = write!(svg, " fill=\"{}\"", hex(palette.resolve(tag.fg_color)));
+                        }
+                        if tag.bold {
+                            svg.push_str(" font-weight=\"bold\"");
+                        }
+                        if tag.italic {
+                            svg.push_str(" font-style=\"italic\"");
+                        }
+                        if tag.underline {
+                            svg.push_str(" text-decoration=\"underline\"");
+                        }
+                    }
+                    let _ = write!(svg, ">{}</text>", escape(text));
+                }
+                if tag.is_some_and(|tag| tag.url.is_some()) {
+                    svg.push_str("</a>");
+                }
+                svg.push('\n');
+            }
+        }
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// Class names and inline declarations for a tagged HTML span
+fn html_attributes(tag: &FormatTag, options: &HtmlOptions) -> (Vec<String>, Vec<String>) {
+    let palette = &options.palette;
+    let prefix = &options.class_prefix;
+    let mut classes = Vec::new();
+    let mut styles = Vec::new();
+    let classes_mode = options.style_mode == StyleMode::Classes;
+
+    if tag.fg_color != SelectGraphicRendition::ForegroundDefault {
+        match ansi_index(tag.fg_color) {
+            Some(i) if classes_mode => classes.push(format!("{prefix}-fg-{i}")),
+            _ => styles.push(format!("color:{}", hex(palette.resolve(tag.fg_color)))),
+        }
+    }
+    if tag.bg_color != SelectGraphicRendition::BackgroundDefault {
+        match ansi_index(tag.bg_color) {
+            Some(i) if classes_mode => classes.push(format!("{prefix}-bg-{i}")),
+            _ => styles.push(format!("background-color:{}", hex(palette.resolve(tag.bg_color)))),
+        }
+    }
+
+    if classes_mode {
+        for (enabled, name) in [
+            (tag.bold, "bold"),
+            (tag.italic, "italic"),
+            (tag.underline, "underline"),
+            (tag.blink, "blink"),
+        ] {
+            if enabled {
+                classes.push(format!("{prefix}-{name}"));
+            }
+        }
+    } else {
+        if tag.bold {
+            styles.push("font-weight:bold".to_string());
+        }
+        if tag.italic {
+            styles.push("font-style:italic".to_string());
+        }
+        let decorations: Vec<&str> = [(tag.underline, "underline"), (tag.blink, "blink")]
+            .into_iter()
+            .filter_map(|(enabled, name)| enabled.then_some(name))
+            .collect();
+        if !decorations.is_empty() {
+            styles.push(format!("text-decoration:{}", decorations.join(" ")));
+        }
+    }
+    (classes, styles)
+}
+
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Escape text for use in HTML/SVG content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
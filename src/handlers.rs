@@ -0,0 +1,137 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::definitions::{StringTerminator, TerminalOutput};
+
+/// A custom event produced by a registered handler and reported as
+/// [`TerminalOutput::Extension`]. Implemented for any `'static` type that is
+/// `Debug + PartialEq + Send + Sync`.
+pub trait ExtensionEvent: Any + Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_event(&self, other: &dyn ExtensionEvent) -> bool;
+}
+
+impl<T: Any + Debug + PartialEq + Send + Sync> ExtensionEvent for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_event(&self, other: &dyn ExtensionEvent) -> bool {
+        other.as_any().downcast_ref::<T>().is_some_and(|other| self == other)
+    }
+}
+
+impl dyn ExtensionEvent {
+    /// The concrete event, if it is a `T`
+    pub fn downcast_ref<T: ExtensionEvent>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn is<T: ExtensionEvent>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+}
+
+impl PartialEq for dyn ExtensionEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_event(other)
+    }
+}
+
+impl Eq for dyn ExtensionEvent {}
+
+/// What a handler produced for a sequence it accepted
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HandlerResponse {
+    /// Reported in place of the raw sequence. May be empty to swallow it.
+    pub events: Vec<TerminalOutput>,
+    /// Bytes to send back to the application, collected by
+    /// [`AnsiParser::take_replies`](crate::AnsiParser::take_replies)
+    pub reply: Vec<u8>,
+}
+
+impl HandlerResponse {
+    pub fn new(events: Vec<TerminalOutput>) -> Self {
+        HandlerResponse { events, reply: Vec::new() }
+    }
+
+    /// A response carrying a single custom event
+    pub fn extension<T: ExtensionEvent>(event: T) -> Self {
+        Self::new(vec![TerminalOutput::Extension(Box::new(event))])
+    }
+
+    pub fn with_reply(mut self, reply: impl Into<Vec<u8>>) -> Self {
+        self.reply = reply.into();
+        self
+    }
+}
+
+/// Handles `OSC Ps ; Pt`. Receives `Pt` (`None` when there is no `;`) and the
+/// terminator used. Returning `None` reports the sequence as a raw `Osc`.
+pub type OscHandler = Box<dyn FnMut(Option<&[u8]>, StringTerminator) -> Option<HandlerResponse> + Send>;
+
+/// Handles a DCS string. Receives the numeric parameters and the data after the
+/// final byte. Returning `None` reports a raw `DeviceControlString`.
+pub type DcsHandler = Box<dyn FnMut(&[usize], &[u8]) -> Option<HandlerResponse> + Send>;
+
+/// Handles an APC string. Receives the payload following the registered prefix.
+/// Returning `None` drops the string, as for unregistered prefixes.
+pub type ApcHandler = Box<dyn FnMut(&[u8]) -> Option<HandlerResponse> + Send>;
+
+#[derive(Default)]
+pub(crate) struct HandlerRegistry {
+    osc: HashMap<usize, OscHandler>,
+    dcs: HashMap<(Vec<u8>, u8), DcsHandler>,
+    apc: Vec<(Vec<u8>, ApcHandler)>,
+}
+
+impl HandlerRegistry {
+    pub(crate) fn set_osc(&mut self, command: usize, handler: OscHandler) {
+        self.osc.insert(command, handler);
+    }
+
+    pub(crate) fn set_dcs(&mut self, intermediates: &[u8], final_byte: u8, handler: DcsHandler) {
+        self.dcs.insert((intermediates.to_vec(), final_byte), handler);
+    }
+
+    pub(crate) fn set_apc(&mut self, prefix: &[u8], handler: ApcHandler) {
+        match self.apc.iter_mut().find(|(p, _)| p == prefix) {
+            Some(entry) => entry.1 = handler,
+            None => self.apc.push((prefix.to_vec(), handler)),
+        }
+    }
+
+    pub(crate) fn osc(
+        &mut self,
+        command: usize,
+        params: Option<&[u8]>,
+        terminator: StringTerminator,
+    ) -> Option<HandlerResponse> {
+        self.osc.get_mut(&command)?(params, terminator)
+    }
+
+    pub(crate) fn dcs(
+        &mut self,
+        intermediates: &[u8],
+        final_byte: u8,
+        params: &[usize],
+        data: &[u8],
+    ) -> Option<HandlerResponse> {
+        // Avoid allocating a key for the common case of no DCS handlers at all
+        if self.dcs.is_empty() {
+            return None;
+        }
+        self.dcs.get_mut(&(intermediates.to_vec(), final_byte))?(params, data)
+    }
+
+    /// The longest registered prefix wins
+    pub(crate) fn apc(&mut self, payload: &[u8]) -> Option<HandlerResponse> {
+        let (prefix, handler) = self
+            .apc
+            .iter_mut()
+            .filter(|(prefix, _)| payload.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())?;
+        handler(&payload[prefix.len()..])
+    }
+}
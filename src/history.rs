@@ -0,0 +1,209 @@
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use crate::definitions::TerminalOutput;
+
+/// Default number of finished commands kept by [`CommandHistory`]
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// One prompt/command/output cycle delimited by OSC 133 (or 633) marks.
+///
+/// Offsets count the text bytes seen by the history: the bytes of every
+/// `Data` plus one for each line feed (`Newline`, `VerticalTab`, `FormFeed`,
+/// `Index` and `NextLine`) and each `HorizontalTab`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// Offset of the prompt mark, the place to jump to for this command
+    pub start: usize,
+    pub prompt: Vec<u8>,
+    pub command: Vec<u8>,
+    /// Offsets of the command's output
+    pub output: Range<usize>,
+    pub exit_code: Option<i32>,
+    /// When the command line was submitted
+    pub executed_at: Option<Instant>,
+    pub finished_at: Option<Instant>,
+    output_text: Vec<u8>,
+}
+
+impl CommandBlock {
+    fn new(start: usize) -> Self {
+        CommandBlock {
+            start,
+            prompt: Vec::new(),
+            command: Vec::new(),
+            output: start..start,
+            exit_code: None,
+            executed_at: None,
+            finished_at: None,
+            output_text: Vec::new(),
+        }
+    }
+
+    /// Output text with line breaks as `\n`
+    pub fn output_text(&self) -> &[u8] {
+        &self.output_text
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        Some(self.finished_at?.saturating_duration_since(self.executed_at?))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Prompt,
+    Input,
+    Output,
+}
+
+/// Groups parser output into [`CommandBlock`]s using shell integration marks.
+///
+/// Text outside a block (before the first prompt, or between a command
+/// finishing and the next prompt) only advances the offset.
+pub struct CommandHistory {
+    blocks: Vec<CommandBlock>,
+    current: Option<(CommandBlock, Phase)>,
+    offset: usize,
+    max_blocks: usize,
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandHistory {
+    pub fn new() -> CommandHistory {
+        CommandHistory {
+            blocks: Vec::new(),
+            current: None,
+            offset: 0,
+            max_blocks: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+
+    /// Keep at most `max_blocks` completed blocks, dropping the oldest
+    pub fn set_max_blocks(&mut self, max_blocks: usize) {
+        self.max_blocks = max_blocks;
+        self.trim();
+    }
+
+    pub fn extend<'a, I>(&mut self, outputs: I, now: Instant)
+    where
+        I: IntoIterator<Item = &'a TerminalOutput>,
+    {
+        for output in outputs {
+            self.record(output, now);
+        }
+    }
+
+    /// Feed one parser output. `now` timestamps command execution and completion.
+    pub fn record(&mut self, output: &TerminalOutput, now: Instant) {
+        match output {
+            TerminalOutput::Data(bytes) => self.text(bytes),
+            TerminalOutput::Newline
+            | TerminalOutput::VerticalTab
+            | TerminalOutput::FormFeed
+            | TerminalOutput::Index
+            | TerminalOutput::NextLine => self.text(b"\n"),
+            TerminalOutput::HorizontalTab => self.text(b"\t"),
+            TerminalOutput::PromptStart => {
+                // A prompt without a `D` for the previous command abandons it
+                self.close();
+                self.current = Some((CommandBlock::new(self.offset), Phase::Prompt));
+            }
+            TerminalOutput::CommandStart => self.enter(Phase::Input),
+            TerminalOutput::CommandExecuted => {
+                self.enter(Phase::Output);
+                if let Some((block, _)) = &mut self.current {
+                    block.output = self.offset..self.offset;
+                    block.executed_at = Some(now);
+                }
+            }
+            TerminalOutput::CommandFinished { exit_code } => {
+                // Shells also send `D` before their very first prompt; ignore it
+                if let Some((block, phase)) = &mut self.current {
+                    if *phase != Phase::Output {
+                        block.output = self.offset..self.offset;
+                    }
+                    block.exit_code = *exit_code;
+                    block.finished_at = Some(now);
+                    self.close();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Completed blocks, oldest first
+    pub fn blocks(&self) -> &[CommandBlock] {
+        &self.blocks
+    }
+
+    /// The block still waiting for its `D` mark
+    pub fn current(&self) -> Option<&CommandBlock> {
+        self.current.as_ref().map(|(block, _)| block)
+    }
+
+    /// Output of the most recent finished command
+    pub fn last_output(&self) -> Option<&[u8]> {
+        self.blocks.iter().rev().find(|b| b.is_finished()).map(|b| b.output_text())
+    }
+
+    /// The closest block starting before `offset`
+    pub fn previous(&self, offset: usize) -> Option<&CommandBlock> {
+        self.iter().rev().find(|b| b.start < offset)
+    }
+
+    /// The closest block starting after `offset`
+    pub fn next(&self, offset: usize) -> Option<&CommandBlock> {
+        self.iter().find(|b| b.start > offset)
+    }
+
+    /// Current offset in the text stream
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.blocks.iter().chain(self.current())
+    }
+
+    fn text(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len();
+        if let Some((block, phase)) = &mut self.current {
+            match phase {
+                Phase::Prompt => block.prompt.extend_from_slice(bytes),
+                Phase::Input => block.command.extend_from_slice(bytes),
+                Phase::Output => {
+                    block.output_text.extend_from_slice(bytes);
+                    block.output.end = self.offset;
+                }
+            }
+        }
+    }
+
+    /// Move the current block to `phase`, starting one if a mark came without `A`
+    fn enter(&mut self, phase: Phase) {
+        let offset = self.offset;
+        self.current.get_or_insert_with(|| (CommandBlock::new(offset), phase)).1 = phase;
+    }
+
+    fn close(&mut self) {
+        if let Some((block, _)) = self.current.take() {
+            self.blocks.push(block);
+            self.trim();
+        }
+    }
+
+    fn trim(&mut self) {
+        let excess = self.blocks.len().saturating_sub(self.max_blocks);
+        self.blocks.drain(..excess);
+    }
+}
@@ -1,8 +1,36 @@
+mod charset;
+mod clipboard;
+mod color;
 mod definitions;
+mod encode;
+mod export;
+mod handlers;
+mod history;
+mod notify;
+mod osc;
 mod parser;
+mod sanitize;
+mod screen;
 mod tables;
 mod tests;
+mod title;
+mod vt52;
+mod window;
 
 // Re-export specific items used by the binaries/GUI
-pub use definitions::{Mode, SelectGraphicRendition, TerminalOutput, CursorShape};
+pub use charset::CodePage;
+pub use clipboard::{clipboard_reply, ClipboardAccess, ClipboardPolicy, DEFAULT_CLIPBOARD_MAX_SIZE};
+pub use color::{dynamic_color_reply, named_color, palette_color_reply, parse_color_spec};
+pub use definitions::{
+    Mode, SelectGraphicRendition, TerminalOutput, CursorShape, FormatTag, Rgb, DynamicColor,
+    Selection, StringTerminator, Urgency, ProgressState, TitleTarget, WindowOp, MaximizeMode, FullscreenMode,
+    ControlEncoding, CharsetIndex, StandardCharset, InputEncoding, Rect, EraseRange, SyncResetPolicy,
+};
+pub use export::{HtmlOptions, Palette, StyleMode, StyledText, SvgOptions};
+pub use handlers::{ApcHandler, DcsHandler, ExtensionEvent, HandlerResponse, OscHandler};
+pub use history::{CommandBlock, CommandHistory, DEFAULT_HISTORY_LIMIT};
 pub use parser::AnsiParser;
+pub use sanitize::{SanitizePolicy, Sanitizer};
+pub use screen::{Cell, LineSize, Screen, Style};
+pub use title::{TitleModes, TitleStack, DEFAULT_TITLE_STACK_DEPTH};
+pub use window::{window_op_reply, WindowOpPolicy, WindowState};
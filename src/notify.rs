@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::clipboard::base64_decode;
+use crate::definitions::{ProgressState, TerminalOutput, Urgency};
+use crate::handlers::{HandlerResponse, OscHandler};
+
+/// Limit for the text of a kitty notification assembled from chunks
+pub(crate) const MAX_NOTIFICATION_LEN: usize = 1 << 16;
+/// Limit for kitty notifications waiting for their final chunk
+const MAX_PENDING_NOTIFICATIONS: usize = 16;
+
+/// OSC 9 ; message (iTerm2), or ConEmu's OSC 9 ; 4 ; state ; percent.
+/// A payload starting with `digits;` is a ConEmu command; the others are left raw.
+pub(crate) fn osc9(params: &str) -> Option<TerminalOutput> {
+    match params.split_once(';') {
+        Some(("4", progress)) => progress_report(progress),
+        Some((command, _)) if !command.is_empty() && command.bytes().all(|b| b.is_ascii_digit()) => None,
+        _ => Some(TerminalOutput::Notification {
+            title: None,
+            body: params.to_string(),
+            id: None,
+            urgency: Urgency::Normal,
+        }),
+    }
+}
+
+/// `state [; percent]`, percent clamped to 100
+fn progress_report(params: &str) -> Option<TerminalOutput> {
+    let mut fields = params.split(';');
+    let state = match fields.next()? {
+        "" | "0" => ProgressState::Hidden,
+        "1" => ProgressState::Normal,
+        "2" => ProgressState::Error,
+        "3" => ProgressState::Indeterminate,
+        "4" => ProgressState::Paused,
+        _ => return None,
+    };
+    let percent = fields.next().and_then(|p| p.parse::<usize>().ok()).map(|p| p.min(100) as u8);
+    Some(TerminalOutput::Progress { state, percent })
+}
+
+/// OSC 777 ; notify ; title ; body (rxvt-unicode). The body may contain `;`.
+pub(crate) fn osc777(params: &str) -> Option<TerminalOutput> {
+    let params = params.strip_prefix("notify;")?;
+    let (title, body) = params.split_once(';').unwrap_or((params, ""));
+    Some(TerminalOutput::Notification {
+        title: (!title.is_empty()).then(|| title.to_string()),
+        body: body.to_string(),
+        id: None,
+        urgency: Urgency::Normal,
+    })
+}
+
+#[derive(Default)]
+struct PendingNotification {
+    title: Option<String>,
+    body: String,
+    urgency: Urgency,
+}
+
+/// OSC 99 ; metadata ; payload (kitty). Chunks sharing an `i=` id are joined
+/// until one arrives with `d=1` (the default).
+pub(crate) fn kitty_handler() -> OscHandler {
+    let mut pending: HashMap<Option<String>, PendingNotification> = HashMap::new();
+    Box::new(move |params, _| {
+        let (metadata, payload) = std::str::from_utf8(params?).ok()?.split_once(';')?;
+
+        let mut id = None;
+        let mut done = true;
+        let mut is_body = false;
+        let mut encoded = false;
+        let mut urgency = None;
+        for (key, value) in metadata.split(':').filter_map(|kv| kv.split_once('=')) {
+            match key {
+                "i" => id = Some(value.to_string()),
+                "d" => done = value != "0",
+                "p" => match value {
+                    "title" => is_body = false,
+                    "body" => is_body = true,
+                    // Queries, icons, buttons, close requests, ...
+                    _ => return None,
+                },
+                "e" => encoded = value == "1",
+                "u" => {
+                    urgency = match value {
+                        "0" => Some(Urgency::Low),
+                        "1" => Some(Urgency::Normal),
+                        "2" => Some(Urgency::Critical),
+                        _ => urgency,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let text = if encoded {
+            String::from_utf8(base64_decode(payload)?).ok()?
+        } else {
+            payload.to_string()
+        };
+
+        if !pending.contains_key(&id) && pending.len() >= MAX_PENDING_NOTIFICATIONS {
+            warn!("Too many unfinished OSC 99 notifications, dropping them");
+            pending.clear();
+        }
+        let notification = pending.entry(id.clone()).or_default();
+        if is_body {
+            notification.body.push_str(&text);
+        } else {
+            notification.title.get_or_insert_with(String::new).push_str(&text);
+        }
+        if let Some(urgency) = urgency {
+            notification.urgency = urgency;
+        }
+
+        let len = notification.title.as_ref().map_or(0, String::len) + notification.body.len();
+        if len > MAX_NOTIFICATION_LEN {
+            warn!("OSC 99 notification exceeds the {} byte limit", MAX_NOTIFICATION_LEN);
+            pending.remove(&id);
+            return Some(HandlerResponse::default());
+        }
+        if !done {
+            return Some(HandlerResponse::default());
+        }
+
+        let PendingNotification { title, body, urgency } = pending.remove(&id)?;
+        Some(HandlerResponse::new(vec![TerminalOutput::Notification { title, body, id, urgency }]))
+    })
+}
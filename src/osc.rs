@@ -0,0 +1,231 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::clipboard::ClipboardConfig;
+use crate::color::parse_color_spec;
+use crate::definitions::{DynamicColor, StringTerminator, TerminalOutput};
+use crate::handlers::{HandlerRegistry, HandlerResponse, OscHandler};
+use crate::notify;
+use crate::title::TitleModes;
+
+/// Split an OSC string into its numeric command and its parameters.
+/// `params` is `None` when the string has no `;` separator at all.
+pub(crate) fn split_command(buffer: &[u8]) -> (Option<usize>, Option<&[u8]>) {
+    let (number, params) = match buffer.iter().position(|&b| b == b';') {
+        Some(idx) => (&buffer[..idx], Some(&buffer[idx + 1..])),
+        None => (buffer, None),
+    };
+    let command = std::str::from_utf8(number).ok().and_then(|n| n.parse::<usize>().ok());
+    (command, params)
+}
+
+/// Install the handlers for the OSC commands we understand
+pub(crate) fn register_builtins(
+    registry: &mut HandlerRegistry,
+    clipboard: Arc<Mutex<ClipboardConfig>>,
+    title_modes: Arc<Mutex<TitleModes>>,
+) {
+    for command in 0..=2 {
+        registry.set_osc(command, title_handler(command, title_modes.clone()));
+    }
+    registry.set_osc(4, typed(|params, terminator| palette_colors(params.unwrap_or_default(), terminator)));
+    registry.set_osc(7, Box::new(|params, _| single(working_directory(params?))));
+    registry.set_osc(8, typed(|params, _| params.and_then(hyperlink).unwrap_or_default()));
+    for command in 10..=12 {
+        registry.set_osc(
+            command,
+            typed(move |params, terminator| dynamic_colors(command, params.unwrap_or_default(), terminator)),
+        );
+    }
+    registry.set_osc(52, clipboard_handler(clipboard));
+    // FinalTerm semantic prompts and VS Code's variant of them
+    for command in [133, 633] {
+        registry.set_osc(command, typed(|params, _| params.and_then(semantic_prompt).into_iter().collect()));
+    }
+    registry.set_osc(1337, Box::new(|params, _| single(iterm_current_dir(params?))));
+    registry.set_osc(9, typed(|params, _| params.and_then(notify::osc9).into_iter().collect()));
+    registry.set_osc(99, notify::kitty_handler());
+    registry.set_osc(777, typed(|params, _| params.and_then(notify::osc777).into_iter().collect()));
+    registry.set_osc(104, typed(|params, _| reset_palette_colors(params)));
+    for kind in [DynamicColor::Foreground, DynamicColor::Background, DynamicColor::Cursor] {
+        registry.set_osc(kind.osc_number() + 100, typed(move |_, _| vec![TerminalOutput::ResetDynamicColor(kind)]));
+    }
+}
+
+/// Adapt a parser of UTF-8 parameters into a handler
+fn typed<F>(parse: F) -> OscHandler
+where
+    F: Fn(Option<&str>, StringTerminator) -> Vec<TerminalOutput> + Send + 'static,
+{
+    Box::new(move |params, terminator| {
+        let params = match params {
+            Some(params) => Some(std::str::from_utf8(params).ok()?),
+            None => None,
+        };
+        let events = parse(params, terminator);
+        // Nothing usable in the string; let the caller see it raw
+        (!events.is_empty()).then(|| HandlerResponse::new(events))
+    })
+}
+
+fn single(item: Option<TerminalOutput>) -> Option<HandlerResponse> {
+    item.map(|item| HandlerResponse::new(vec![item]))
+}
+
+/// OSC 0 sets both the icon name and the title, OSC 1 the icon name, OSC 2 the title
+fn title_handler(command: usize, title_modes: Arc<Mutex<TitleModes>>) -> OscHandler {
+    Box::new(move |params, _| {
+        let title = title_modes.lock().unwrap_or_else(PoisonError::into_inner).decode(params?)?;
+        let events = match command {
+            0 => vec![TerminalOutput::SetIconName(title.clone()), TerminalOutput::SetTitle(title)],
+            1 => vec![TerminalOutput::SetIconName(title)],
+            _ => vec![TerminalOutput::SetTitle(title)],
+        };
+        Some(HandlerResponse::new(events))
+    })
+}
+
+fn clipboard_handler(clipboard: Arc<Mutex<ClipboardConfig>>) -> OscHandler {
+    Box::new(move |params, terminator| {
+        let params = std::str::from_utf8(params?).ok()?;
+        if !params.contains(';') {
+            return None;
+        }
+        let config = clipboard.lock().unwrap_or_else(PoisonError::into_inner);
+        // A rejected clipboard request produces nothing at all, not a raw Osc
+        Some(HandlerResponse::new(config.parse(params, terminator).into_iter().collect()))
+    })
+}
+
+/// OSC 8 ; params ; URI - an empty URI ends the current link
+fn hyperlink(params: &str) -> Option<Vec<TerminalOutput>> {
+    let (params, uri) = params.split_once(';')?;
+    if uri.is_empty() {
+        return Some(vec![TerminalOutput::ClearHyperlink]);
+    }
+    // Parse ID from params (format: key=value:key=value)
+    let id = params
+        .split(':')
+        .find_map(|kv| kv.strip_prefix("id="))
+        .map(|id| id.to_string());
+    Some(vec![TerminalOutput::SetHyperlink { id, uri: uri.to_string() }])
+}
+
+/// OSC 7 ; file://host/path - kitty's `kitty-shell-cwd://` URLs carry the path
+/// without percent-encoding. Other schemes and relative paths are left raw.
+fn working_directory(url: &[u8]) -> Option<TerminalOutput> {
+    let (rest, encoded) = if let Some(rest) = strip_prefix_ignore_case(url, b"file://") {
+        (rest, true)
+    } else {
+        (strip_prefix_ignore_case(url, b"kitty-shell-cwd://")?, false)
+    };
+    let (host, path) = rest.split_at(rest.iter().position(|&b| b == b'/')?);
+    let host = (!host.is_empty()).then(|| String::from_utf8_lossy(&percent_decode(host)).into_owned());
+    let path = if encoded { percent_decode(path) } else { path.to_vec() };
+    Some(TerminalOutput::WorkingDirectory { host, path })
+}
+
+/// OSC 1337 ; CurrentDir=path - the other iTerm2 commands are left raw
+fn iterm_current_dir(params: &[u8]) -> Option<TerminalOutput> {
+    let path = params.strip_prefix(b"CurrentDir=").filter(|path| !path.is_empty())?;
+    Some(TerminalOutput::WorkingDirectory { host: None, path: path.to_vec() })
+}
+
+fn strip_prefix_ignore_case<'a>(bytes: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+    let head = bytes.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &bytes[prefix.len()..])
+}
+
+/// Decode `%XX` escapes. Malformed escapes are kept as they are.
+pub(crate) fn percent_decode(bytes: &[u8]) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = match bytes.get(i..i + 3) {
+            Some(&[b'%', hi, lo]) => hex(hi).zip(hex(lo)).map(|(hi, lo)| hi << 4 | lo),
+            _ => None,
+        };
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Escape everything except unreserved characters and `/`
+pub(crate) fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// OSC 4 ; index ; spec [; index ; spec ...]
+fn palette_colors(params: &str, terminator: StringTerminator) -> Vec<TerminalOutput> {
+    let mut items = Vec::new();
+    let mut fields = params.split(';');
+    while let (Some(index), Some(spec)) = (fields.next(), fields.next()) {
+        let Ok(index) = index.parse::<u8>() else {
+            continue;
+        };
+        if spec == "?" {
+            items.push(TerminalOutput::QueryPaletteColor { index, terminator });
+        } else if let Some(color) = parse_color_spec(spec) {
+            items.push(TerminalOutput::SetPaletteColor { index, color });
+        }
+    }
+    items
+}
+
+/// OSC 10 ; spec [; spec ...] - each extra spec applies to the next colour (11, then 12)
+fn dynamic_colors(command: usize, params: &str, terminator: StringTerminator) -> Vec<TerminalOutput> {
+    let kinds = [DynamicColor::Foreground, DynamicColor::Background, DynamicColor::Cursor];
+    kinds[command - 10..]
+        .iter()
+        .zip(params.split(';'))
+        .filter_map(|(&kind, spec)| {
+            if spec == "?" {
+                Some(TerminalOutput::QueryDynamicColor { kind, terminator })
+            } else {
+                parse_color_spec(spec).map(|color| TerminalOutput::SetDynamicColor { kind, color })
+            }
+        })
+        .collect()
+}
+
+/// OSC 133 ; A | B | C | D [; exit] - further options such as `aid=` are ignored
+fn semantic_prompt(params: &str) -> Option<TerminalOutput> {
+    let mut fields = params.split(';');
+    let item = match fields.next()? {
+        "A" => TerminalOutput::PromptStart,
+        "B" => TerminalOutput::CommandStart,
+        "C" => TerminalOutput::CommandExecuted,
+        "D" => TerminalOutput::CommandFinished { exit_code: fields.next().and_then(|code| code.parse().ok()) },
+        _ => return None,
+    };
+    Some(item)
+}
+
+/// OSC 104 [; index ...] - no indexes resets the whole palette
+fn reset_palette_colors(params: Option<&str>) -> Vec<TerminalOutput> {
+    match params.filter(|p| !p.is_empty()) {
+        None => vec![TerminalOutput::ResetPaletteColor(None)],
+        Some(params) => params
+            .split(';')
+            .filter_map(|index| index.parse::<u8>().ok())
+            .map(|index| TerminalOutput::ResetPaletteColor(Some(index)))
+            .collect(),
+    }
+}
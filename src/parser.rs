@@ -1,7 +1,23 @@
-use crate::definitions::{CharsetIndex, Mode, SelectGraphicRendition, StandardCharset, TerminalOutput};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::charset::CodePage;
+use crate::clipboard::{ClipboardAccess, ClipboardConfig};
+use crate::definitions::{
+    CharsetIndex, ControlEncoding, EraseRange, InputEncoding, Mode, Rect, SelectGraphicRendition, Selection, StandardCharset, StringTerminator,
+    SyncResetPolicy, TerminalOutput,
+    TitleTarget, WindowOp,
+};
+use crate::handlers::{HandlerRegistry, HandlerResponse};
+use crate::osc;
 use crate::tables::{Action, State, CLASS_TABLE, TRANSITION_TABLE};
+use crate::title::TitleModes;
+use crate::vt52::{Vt52Action, Vt52State, VT52_IDENTIFY_REPLY};
+use crate::window::{parse_window_op, WindowOpPolicy};
 use log::{debug, warn};
 
+/// Shown for SUB: the reversed question mark DEC terminals display
+const SUBSTITUTE_GLYPH: char = '\u{2426}';
+
 pub struct AnsiParser {
     pub(crate) state: State,
     pub(crate) params: Vec<usize>,
@@ -9,7 +25,7 @@ pub struct AnsiParser {
     pub(crate) intermediates: Vec<u8>,
     // Buffer to collect printable data to send in chunks
     pub(crate) data_buffer: Vec<u8>,
-    // Buffers for string sequences
+    // Buffers for string sequences (OSC and APC share `osc_buffer`)
     osc_buffer: Vec<u8>,
     dcs_buffer: Vec<u8>,
     dcs_params_cache: Vec<usize>,
@@ -17,11 +33,48 @@ pub struct AnsiParser {
     // Character set state
     active_charset: CharsetIndex,
     charsets: [StandardCharset; 4],
+    // Charset invoked into GR, a pending single shift, and DECNRCM
+    active_gr_charset: CharsetIndex,
+    single_shift: Option<CharsetIndex>,
+    nrc_mode: bool,
+    // Bytes of a UTF-8 character that isn't complete yet
+    partial_char: Vec<u8>,
+    // LNM, set by `CSI 20 h`, and DECLRMM
+    line_feed_new_line: bool,
+    left_right_margin_mode: bool,
+    // Set by `ESC % G` / `ESC % @`; single-byte input uses Latin-1 without a code page
+    input_encoding: InputEncoding,
+    code_page: Option<CodePage>,
     // Synchronized update state
     sync_update_depth: usize,
     sync_buffer: Vec<TerminalOutput>,
+    sync_reset_policy: SyncResetPolicy,
     // Preceding character for repeat
     preceding_char: Option<char>,
+    // OSC 52 limits and access policy, shared with the built-in OSC 52 handler
+    clipboard: Arc<Mutex<ClipboardConfig>>,
+    // xterm title modes, shared with the built-in OSC 0/1/2 handlers
+    title_modes: Arc<Mutex<TitleModes>>,
+    // Which XTWINOPS requests are reported
+    window_op_policy: WindowOpPolicy,
+    // Built-in and user-registered OSC/DCS/APC handlers
+    handlers: HandlerRegistry,
+    // Bytes handlers asked to send back to the application
+    replies: Vec<u8>,
+    // 8-bit C1 controls in the input, and the UTF-8 bytes still expected
+    c1_controls: bool,
+    utf8_continuation: u8,
+    // Set by S7C1T/S8C1T
+    control_encoding: ControlEncoding,
+    // VT52 grammar state, `None` in ANSI mode
+    vt52: Option<Vt52State>,
+    // VT52 `ESC F` graphics set, kept apart from G0 so leaving VT52 restores it
+    vt52_graphics: bool,
+    // Reply to ENQ
+    answerback: Vec<u8>,
+    // Input from an untrusted source can't hold output back or switch the
+    // grammar or input encoding
+    untrusted: bool,
 }
 
 impl Default for AnsiParser {
@@ -32,6 +85,10 @@ impl Default for AnsiParser {
 
 impl AnsiParser {
     pub fn new() -> AnsiParser {
+        let clipboard = Arc::new(Mutex::new(ClipboardConfig::default()));
+        let title_modes = Arc::new(Mutex::new(TitleModes::default()));
+        let mut handlers = HandlerRegistry::default();
+        osc::register_builtins(&mut handlers, clipboard.clone(), title_modes.clone());
         AnsiParser {
             state: State::Ground,
             params: Vec::with_capacity(8),
@@ -44,11 +101,209 @@ impl AnsiParser {
             dcs_intermediates_cache: Vec::with_capacity(4),
             active_charset: CharsetIndex::G0,
             charsets: [StandardCharset::Ascii; 4],
+            active_gr_charset: CharsetIndex::G2,
+            single_shift: None,
+            nrc_mode: false,
+            partial_char: Vec::with_capacity(4),
+            line_feed_new_line: false,
+            left_right_margin_mode: false,
+            input_encoding: InputEncoding::Utf8,
+            code_page: None,
             sync_update_depth: 0,
             sync_buffer: Vec::new(),
+            sync_reset_policy: SyncResetPolicy::Flush,
             preceding_char: None,
+            clipboard,
+            title_modes,
+            window_op_policy: Box::new(WindowOp::is_safe),
+            handlers,
+            replies: Vec::new(),
+            c1_controls: false,
+            utf8_continuation: 0,
+            control_encoding: ControlEncoding::SevenBit,
+            vt52: None,
+            vt52_graphics: false,
+            answerback: Vec::new(),
+            untrusted: false,
         }
     }
+
+    /// Limit the decoded size of OSC 52 clipboard writes. Larger writes are dropped.
+    pub fn set_clipboard_max_size(&mut self, max_size: usize) {
+        self.clipboard.lock().unwrap_or_else(PoisonError::into_inner).max_size = max_size;
+    }
+
+    /// Install a hook deciding which OSC 52 reads and writes are reported.
+    /// Denied requests produce no output. Without a policy everything is allowed.
+    pub fn set_clipboard_policy<F>(&mut self, policy: F)
+    where
+        F: Fn(ClipboardAccess, &[Selection]) -> bool + Send + 'static,
+    {
+        self.clipboard.lock().unwrap_or_else(PoisonError::into_inner).policy = Some(Box::new(policy));
+    }
+
+    /// Title modes set by `CSI > Ps t`. The query modes decide how title reports should be encoded.
+    pub fn title_modes(&self) -> TitleModes {
+        *self.title_modes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Install the allow-list for XTWINOPS (`CSI Ps t`). Denied operations produce
+    /// no output. By default only size, position and state reports are allowed.
+    pub fn set_window_op_policy<F>(&mut self, policy: F)
+    where
+        F: Fn(&WindowOp) -> bool + Send + 'static,
+    {
+        self.window_op_policy = Box::new(policy);
+    }
+
+    /// Handle `OSC command ; ...` with `handler`, replacing any built-in handling
+    /// of that command.
+    pub fn register_osc_handler<F>(&mut self, command: usize, handler: F)
+    where
+        F: FnMut(Option<&[u8]>, StringTerminator) -> Option<HandlerResponse> + Send + 'static,
+    {
+        self.handlers.set_osc(command, Box::new(handler));
+    }
+
+    /// Handle DCS strings with the given intermediates and final byte
+    pub fn register_dcs_handler<F>(&mut self, intermediates: &[u8], final_byte: u8, handler: F)
+    where
+        F: FnMut(&[usize], &[u8]) -> Option<HandlerResponse> + Send + 'static,
+    {
+        self.handlers.set_dcs(intermediates, final_byte, Box::new(handler));
+    }
+
+    /// Handle APC strings starting with `prefix`. APC strings are otherwise ignored.
+    pub fn register_apc_handler<F>(&mut self, prefix: &[u8], handler: F)
+    where
+        F: FnMut(&[u8]) -> Option<HandlerResponse> + Send + 'static,
+    {
+        self.handlers.set_apc(prefix, Box::new(handler));
+    }
+
+    /// Recognise 8-bit C1 controls (0x80-0x9F) in the input, e.g. 0x9B as CSI.
+    /// Bytes continuing a UTF-8 sequence are never treated as controls.
+    pub fn set_c1_controls(&mut self, enabled: bool) {
+        self.c1_controls = enabled;
+        self.utf8_continuation = 0;
+    }
+
+    /// Encoding for replies, switched by `ESC SP F` (S7C1T) and `ESC SP G` (S8C1T).
+    /// Replies from handlers are converted automatically; use
+    /// [`ControlEncoding::convert`] for replies generated elsewhere.
+    pub fn control_encoding(&self) -> ControlEncoding {
+        self.control_encoding
+    }
+
+    /// Switch between VT52 and ANSI mode. Applications do this themselves with
+    /// `CSI ? 2 l` and `ESC <`.
+    pub fn set_vt52_mode(&mut self, enabled: bool) {
+        self.vt52 = enabled.then(Vt52State::default);
+        self.vt52_graphics = false;
+        self.state = State::Ground;
+        self.clear_state();
+    }
+
+    pub fn is_vt52_mode(&self) -> bool {
+        self.vt52.is_some()
+    }
+
+    /// Read the input as UTF-8 or one byte per character. Applications switch
+    /// with `ESC % G` and `ESC % @`. `Data` is UTF-8 either way.
+    pub fn set_input_encoding(&mut self, encoding: InputEncoding) {
+        self.input_encoding = encoding;
+        self.flush_partial_char();
+        self.utf8_continuation = 0;
+    }
+
+    pub fn input_encoding(&self) -> InputEncoding {
+        self.input_encoding
+    }
+
+    /// Decode bytes 0x80-0xFF of single-byte input with `code_page` instead of Latin-1
+    pub fn set_code_page<F>(&mut self, code_page: F)
+    where
+        F: Fn(u8) -> char + Send + 'static,
+    {
+        self.code_page = Some(Box::new(code_page));
+    }
+
+    /// Whether output held back by a synchronized update is flushed or dropped
+    /// when the parser is reset, by [`AnsiParser::reset`] or `ESC c`
+    pub fn set_sync_reset_policy(&mut self, policy: SyncResetPolicy) {
+        self.sync_reset_policy = policy;
+    }
+
+    /// Return to the initial state, as `ESC c` does. Resets:
+    ///
+    /// - half-parsed sequences, strings and UTF-8 characters, which are dropped
+    /// - G0-G3 designations, GL/GR invocations and pending single shifts
+    /// - DECNRCM, LNM and DECLRMM, VT52 mode and the control encoding
+    /// - xterm title modes and the character repeated by REP
+    /// - synchronized updates, whose held output is returned or dropped
+    ///   according to the [`SyncResetPolicy`]
+    ///
+    /// Handlers, policies, limits, C1 recognition, the input encoding, the code
+    /// page and the answerback message are configuration and are kept, as are
+    /// untaken replies.
+    pub fn reset(&mut self) -> Vec<TerminalOutput> {
+        let mut output = Vec::new();
+        self.reset_state(&mut output);
+        output
+    }
+
+    fn reset_state(&mut self, output: &mut Vec<TerminalOutput>) {
+        if self.sync_update_depth > 0 {
+            match self.sync_reset_policy {
+                SyncResetPolicy::Flush => self.flush_sync_buffer(output),
+                SyncResetPolicy::Discard => self.sync_buffer.clear(),
+            }
+            self.sync_update_depth = 0;
+            output.push(TerminalOutput::EndSynchronizedUpdate);
+        }
+        self.state = State::Ground;
+        self.clear_state();
+        self.osc_buffer.clear();
+        self.dcs_buffer.clear();
+        self.dcs_params_cache.clear();
+        self.dcs_intermediates_cache.clear();
+        self.partial_char.clear();
+        self.utf8_continuation = 0;
+        self.reset_charsets();
+        self.nrc_mode = false;
+        self.line_feed_new_line = false;
+        self.left_right_margin_mode = false;
+        self.preceding_char = None;
+        *self.title_modes.lock().unwrap_or_else(PoisonError::into_inner) = TitleModes::default();
+        self.control_encoding = ControlEncoding::SevenBit;
+        self.vt52 = None;
+        self.vt52_graphics = false;
+    }
+
+    /// Charset state restored by both RIS and DECSTR
+    fn reset_charsets(&mut self) {
+        self.charsets = [StandardCharset::Ascii; 4];
+        self.active_charset = CharsetIndex::G0;
+        self.active_gr_charset = CharsetIndex::G2;
+        self.single_shift = None;
+    }
+
+    /// Message sent back for ENQ (0x05). Empty by default, like xterm.
+    pub fn set_answerback(&mut self, answerback: &[u8]) {
+        self.answerback = answerback.to_vec();
+    }
+
+    /// Used by the sanitizer: synchronized updates aren't buffered, and DECANM
+    /// and `ESC % G` / `ESC % @` are reported without taking effect
+    pub(crate) fn set_untrusted(&mut self, untrusted: bool) {
+        self.untrusted = untrusted;
+    }
+
+    /// Take the reply bytes handlers produced since the last call
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
     // helper for parameter retrieval
     pub(crate) fn get_param(&self, index: usize, default: usize) -> usize {
         *self.params.get(index).unwrap_or(&default)
@@ -58,6 +313,11 @@ impl AnsiParser {
         self.params.get(index).copied()
     }
 
+    /// Margin parameter, where omitted and 0 both select the default
+    fn get_margin(&self, index: usize) -> Option<usize> {
+        self.get_param_opt(index).filter(|&margin| margin != 0)
+    }
+
     pub(crate) fn flush_data(&mut self, output: &mut Vec<TerminalOutput>) {
         if !self.data_buffer.is_empty() {
             output.push(TerminalOutput::Data(std::mem::take(&mut self.data_buffer)));
@@ -89,331 +349,443 @@ impl AnsiParser {
         let mut output = Vec::new();
 
         for &byte in incoming {
-            // 1. Classify Byte
-            let class_idx = CLASS_TABLE[byte as usize] as usize;
-            //2. lookup Transition
-            let entry = TRANSITION_TABLE[self.state as usize][class_idx];
-            // Unpack
-            let next_state_u8 = entry >> 4;
-            let action_u8 = entry & 0x0F;
-
-            // Safety: The tables are constructed with strict repr(u8) and bounds
-            let next_state: State = unsafe { std::mem::transmute(next_state_u8) };
-            let action: Action = unsafe { std::mem::transmute(action_u8) };
-
-            // 3. Perform Action
-            match action {
-                Action::None | Action::Ignore => {}
-                Action::Print => {
-                    // Apply charset mapping
-                    if let Ok(c) = std::str::from_utf8(&[byte]) {
-                        if let Some(ch) = c.chars().next() {
-                            let mapped = self.map_char(ch);
-                            self.data_buffer.extend(mapped.to_string().as_bytes());
-                            self.preceding_char = Some(ch);
-                        }
-                    } else {
-                        self.data_buffer.push(byte);
-                    }
-                }
-                Action::Execute => {
-                    self.flush_data(&mut output);
-                    match byte {
-                        0x08 | 0x7f => self.emit_output(&mut output, TerminalOutput::Backspace),
-                        0x0A | 0x0B | 0x0C => self.emit_output(&mut output, TerminalOutput::Newline),
-                        0x0D => self.emit_output(&mut output, TerminalOutput::CarriageReturn),
-                        0x0E => {
-                            // Shift Out - activate G1
-                            self.active_charset = CharsetIndex::G1;
-                            self.emit_output(
-                                &mut output,
-                                TerminalOutput::SetActiveCharset(CharsetIndex::G1),
-                            );
-                        }
-                        0x0F => {
-                            // Shift In - activate G0
-                            self.active_charset = CharsetIndex::G0;
-                            self.emit_output(
-                                &mut output,
-                                TerminalOutput::SetActiveCharset(CharsetIndex::G0),
-                            );
-                        }
-                        0x11 | 0x12 | 0x13 | 0x14 => {
-                            self.emit_output(&mut output, TerminalOutput::DeviceControl { code: byte });
-                        }
-                        _ => debug!("Unhandled C0 execute: {:02X}", byte),
-                    }
-                }
-                Action::Clear => {
-                    self.flush_data(&mut output);
-                    self.clear_state();
-                }
-                Action::Collect => self.intermediates.push(byte),
-                Action::Param => {
-                    // If byte is ';', push the current param and reset
-                    if byte == b';' {
-                        self.params.push(self.current_param.unwrap_or(0));
-                        self.current_param = None;
-                    } else if byte.is_ascii_digit() {
-                        let digit = (byte - b'0') as usize;
-                        self.current_param = Some(
-                            self.current_param
-                                .unwrap_or(0)
-                                .saturating_mul(10)
-                                .saturating_add(digit),
-                        );
-                    }
-                }
-                Action::EscDispatch => {
-                    self.flush_data(&mut output);
-                    self.perform_esc_dispatch(byte, &mut output);
-                    self.clear_state();
-                }
-                Action::CsiDispatch => {
-                    self.flush_data(&mut output);
-                    // Push the last parameter if it exists
-                    if let Some(p) = self.current_param {
-                        self.params.push(p);
-                    } else if !self.params.is_empty() {
-                        // Handle edge case: [;;m where last param is implicit default 0
-                        self.params.push(0);
-                    }
-                    self.perform_csi_dispatch(byte, &mut output);
-                    self.clear_state();
+            if self.c1_controls && self.is_c1_control(byte) {
+                // Process as the equivalent 7-bit `ESC Fe` sequence
+                self.advance(0x1B, &mut output);
+                self.advance(byte - 0x40, &mut output);
+            } else {
+                self.advance(byte, &mut output);
+            }
+        }
+        // flush any remaining text data after processing the chunk
+        self.flush_data(&mut output);
+        output
+    }
+
+    /// Track UTF-8 sequences so that their continuation bytes are never mistaken
+    /// for C1 controls
+    fn is_c1_control(&mut self, byte: u8) -> bool {
+        if self.input_encoding == InputEncoding::SingleByte {
+            return (0x80..=0x9F).contains(&byte);
+        }
+        match byte {
+            0x80..=0xBF if self.utf8_continuation > 0 => {
+                self.utf8_continuation -= 1;
+                false
+            }
+            0x80..=0x9F => true,
+            0xC2..=0xDF => {
+                self.utf8_continuation = 1;
+                false
+            }
+            0xE0..=0xEF => {
+                self.utf8_continuation = 2;
+                false
+            }
+            0xF0..=0xF4 => {
+                self.utf8_continuation = 3;
+                false
+            }
+            _ => {
+                self.utf8_continuation = 0;
+                false
+            }
+        }
+    }
+
+    fn advance(&mut self, byte: u8, output: &mut Vec<TerminalOutput>) {
+        if let Some(vt52) = self.vt52.as_mut() {
+            let action = vt52.advance(byte);
+            self.perform_vt52_action(action, output);
+            return;
+        }
+
+        // 1. Classify Byte
+        let class_idx = CLASS_TABLE[byte as usize] as usize;
+        //2. lookup Transition
+        let entry = TRANSITION_TABLE[self.state as usize][class_idx];
+        // Unpack
+        let next_state_u8 = entry >> 4;
+        let action_u8 = entry & 0x0F;
+
+        // The tables are constructed with strict repr(u8) and bounds
+        let next_state = State::from_u8(next_state_u8).unwrap_or(State::Ground);
+        let action = Action::from_u8(action_u8).unwrap_or(Action::None);
+
+        if action != Action::Print {
+            self.flush_partial_char();
+        }
+
+        // 3. Perform Action
+        match action {
+            Action::None | Action::Ignore => {}
+            Action::Print => self.print(byte),
+            Action::Execute => self.execute(byte, output),
+            Action::Clear => {
+                self.flush_data(output);
+                self.clear_state();
+            }
+            Action::Collect => self.intermediates.push(byte),
+            Action::Param => {
+                // If byte is ';', push the current param and reset
+                if byte == b';' {
+                    self.params.push(self.current_param.unwrap_or(0));
+                    self.current_param = None;
+                } else if byte.is_ascii_digit() {
+                    let digit = (byte - b'0') as usize;
+                    self.current_param = Some(
+                        self.current_param
+                            .unwrap_or(0)
+                            .saturating_mul(10)
+                            .saturating_add(digit),
+                    );
+                } else if (b'<'..=b'?').contains(&byte) {
+                    // Private markers are kept with the intermediates so
+                    // `CSI ? 25 h` and `CSI > 4 m` don't dispatch as ANSI sequences
+                    self.intermediates.push(byte);
                 }
-                // Reset OSC buffer if we were to implement OSC collection
-                Action::OscStart => {
-                    self.flush_data(&mut output);
-                    self.osc_buffer.clear();
+            }
+            Action::EscDispatch => {
+                self.flush_data(output);
+                self.perform_esc_dispatch(byte, output);
+                self.clear_state();
+            }
+            Action::CsiDispatch => {
+                self.flush_data(output);
+                // Push the last parameter if it exists
+                if let Some(p) = self.current_param {
+                    self.params.push(p);
+                } else if !self.params.is_empty() {
+                    // Handle edge case: [;;m where last param is implicit default 0
+                    self.params.push(0);
                 }
+                self.perform_csi_dispatch(byte, output);
+                self.clear_state();
+            }
+            // Reset OSC buffer if we were to implement OSC collection
+            Action::OscStart => {
+                self.flush_data(output);
+                self.osc_buffer.clear();
+            }
 
-                Action::OscPut => {
-                    // Collect OSC string chars
-                    self.osc_buffer.push(byte);
-                }
+            Action::OscPut => {
+                // Collect OSC string chars
+                self.osc_buffer.push(byte);
+            }
 
-                Action::OscEnd => {
+            Action::OscEnd => {
+                if self.state == State::SosPmApcString {
+                    self.perform_apc_dispatch(output);
+                } else {
                     // OSC format is usually: <Int>;<Text>
                     // Example: \x1b]0;Terminal Title\x07
-                    if !self.osc_buffer.is_empty() {
-                        // Find the separator ';'
-                        let (command, payload) = match self.osc_buffer.iter().position(|&b| b == b';') {
-                            Some(idx) => {
-                                let num_slice = &self.osc_buffer[..idx];
-                                let payload_slice = &self.osc_buffer[idx + 1..];
-
-                                // Parse the command number (default to 0 if invalid)
-                                let cmd_str = std::str::from_utf8(num_slice).unwrap_or("0");
-                                let cmd = cmd_str.parse::<usize>().unwrap_or(0);
-                                (cmd, payload_slice.to_vec())
-                            }
-                            None => {
-                                // Edge case: OSC without a payload or separator
-                                // Try to parse the entire buffer as command number
-                                let cmd_str = std::str::from_utf8(&self.osc_buffer).unwrap_or("0");
-                                let cmd = cmd_str.parse::<usize>().unwrap_or(0);
-                                (cmd, self.osc_buffer.clone())
-                            }
-                        };
-
-                        output.push(TerminalOutput::Osc { command, payload });
-                    }
-                    self.osc_buffer.clear();
+                    let terminator = if byte == 0x1B { StringTerminator::St } else { StringTerminator::Bel };
+                    self.perform_osc_dispatch(terminator, output);
                 }
-                // --- DCS Implementation ---
-                Action::Hook => {
-                    self.flush_data(&mut output);
-
-                    // 1. Finalize the last parameter being parsed (just like CsiDispatch)
-                    if let Some(p) = self.current_param {
-                        self.params.push(p);
-                    } else if !self.params.is_empty() {
-                        // Handle implicit default 0
-                        self.params.push(0);
-                    }
-
-                    // 2. Store current state into DCS caches
-                    self.dcs_params_cache = self.params.clone();
-                    self.dcs_intermediates_cache = self.intermediates.clone();
+                self.osc_buffer.clear();
+            }
+            // --- DCS Implementation ---
+            Action::Hook => {
+                self.flush_data(output);
 
-                    // 3. Clear the buffer for the upcoming data string
-                    self.dcs_buffer.clear();
-                    // Important: The 'byte' here is the Final character (e.g., 'q' or 't').
-                    // It is technically part of the data payload start or command identifier.
-                    // Tests expect it to be in the data.
-                    self.dcs_buffer.push(byte);
+                // 1. Finalize the last parameter being parsed (just like CsiDispatch)
+                if let Some(p) = self.current_param {
+                    self.params.push(p);
+                } else if !self.params.is_empty() {
+                    // Handle implicit default 0
+                    self.params.push(0);
                 }
 
-                Action::Put => {
-                    // Collect the raw data bytes of the DCS string
-                    self.dcs_buffer.push(byte);
-                }
+                // 2. Store current state into DCS caches
+                self.dcs_params_cache = self.params.clone();
+                self.dcs_intermediates_cache = self.intermediates.clone();
 
-                Action::Unhook => {
-                    // Emit the full package: Params + Intermediates + Data
-                    // Take ownership first to avoid multiple mutable borrows
-                    let dcs_output = TerminalOutput::DeviceControlString {
-                        params: std::mem::take(&mut self.dcs_params_cache),
-                        intermediates: std::mem::take(&mut self.dcs_intermediates_cache),
-                        data: std::mem::take(&mut self.dcs_buffer),
-                    };
-                    self.emit_output(&mut output, dcs_output);
-                }
+                // 3. Clear the buffer for the upcoming data string
+                self.dcs_buffer.clear();
+                // Important: The 'byte' here is the Final character (e.g., 'q' or 't').
+                // It is technically part of the data payload start or command identifier.
+                // Tests expect it to be in the data.
+                self.dcs_buffer.push(byte);
+            }
+
+            Action::Put => {
+                // Collect the raw data bytes of the DCS string
+                self.dcs_buffer.push(byte);
             }
-            // 4. Transition State
-            self.state = next_state;
+
+            Action::Unhook => self.perform_dcs_dispatch(output),
         }
-        // flush any remaining text data after processing the chunk
-        self.flush_data(&mut output);
-        output
+        // 4. Transition State
+        self.state = next_state;
     }
 
-    fn map_char(&self, c: char) -> char {
-        let charset = match self.active_charset {
-            CharsetIndex::G0 => self.charsets[0],
-            CharsetIndex::G1 => self.charsets[1],
-            CharsetIndex::G2 => self.charsets[2],
-            CharsetIndex::G3 => self.charsets[3],
-        };
-        charset.map(c)
+    fn perform_vt52_action(&mut self, action: Vt52Action, output: &mut Vec<TerminalOutput>) {
+        if !matches!(action, Vt52Action::Print(_)) {
+            self.flush_partial_char();
+        }
+        match action {
+            Vt52Action::None => {}
+            Vt52Action::Print(byte) => self.print(byte),
+            Vt52Action::Execute(byte) => self.execute(byte, output),
+            Vt52Action::Dispatch(item) => {
+                self.flush_data(output);
+                self.emit_output(output, item);
+            }
+            Vt52Action::Graphics(enabled) => self.vt52_graphics = enabled,
+            Vt52Action::Identify => self.replies.extend_from_slice(VT52_IDENTIFY_REPLY),
+            Vt52Action::ExitVt52 => {
+                self.flush_data(output);
+                self.vt52 = None;
+                self.vt52_graphics = false;
+                self.emit_output(output, TerminalOutput::SetMode(Mode::Ansi));
+            }
+        }
     }
 
-    fn perform_osc_dispatch(&mut self, output: &mut Vec<TerminalOutput>) {
-        if self.osc_buffer.is_empty() {
+    fn print(&mut self, byte: u8) {
+        if byte.is_ascii() {
+            self.flush_partial_char();
+            self.print_char(char::from(byte), false);
             return;
         }
+        if self.input_encoding == InputEncoding::SingleByte {
+            // Only raw GR bytes go through the GR charset; a code page decides for itself
+            match &self.code_page {
+                Some(code_page) => {
+                    let ch = code_page(byte);
+                    self.print_char(ch, false);
+                }
+                None => self.print_char(char::from(byte), true),
+            }
+            return;
+        }
+        // Collect UTF-8 sequences so that single shifts apply to whole characters
+        if !(0x80..=0xBF).contains(&byte) {
+            self.flush_partial_char();
+        }
+        self.partial_char.push(byte);
+        match std::str::from_utf8(&self.partial_char) {
+            Ok(s) => {
+                let ch = s.chars().next().unwrap_or_default();
+                self.partial_char.clear();
+                self.print_char(ch, false);
+            }
+            // Wait for the rest of the character
+            Err(e) if e.error_len().is_none() => {}
+            Err(_) => self.flush_partial_char(),
+        }
+    }
+
+    /// Pass on the bytes of an incomplete UTF-8 sequence unchanged
+    fn flush_partial_char(&mut self) {
+        if !self.partial_char.is_empty() {
+            // An invalid character still uses up a single shift
+            self.single_shift = None;
+            self.data_buffer.append(&mut self.partial_char);
+        }
+    }
 
-        // Find the separator ';'
-        let (command, payload) = match self.osc_buffer.iter().position(|&b| b == b';') {
-            Some(idx) => {
-                let num_slice = &self.osc_buffer[..idx];
-                let payload_slice = &self.osc_buffer[idx + 1..];
+    /// `gr` is set for raw single-byte input in 0xA0-0xFF, the only characters
+    /// the GR charset applies to
+    fn print_char(&mut self, ch: char, gr: bool) {
+        // Apply charset mapping
+        let shifted = self.single_shift.take().map(|index| self.charsets[index as usize]);
+        let gl = if self.vt52_graphics {
+            StandardCharset::SpecialCharacterAndLineDrawing
+        } else {
+            self.charsets[self.active_charset as usize]
+        };
+        let mapped = match ch {
+            ' '..='~' => shifted.unwrap_or(gl).map(ch),
+            '\u{A0}'..='\u{FF}' if gr => shifted.unwrap_or(self.charsets[self.active_gr_charset as usize]).map_gr(ch),
+            _ => ch,
+        };
+        let mut buf = [0; 4];
+        self.data_buffer.extend_from_slice(mapped.encode_utf8(&mut buf).as_bytes());
+        self.preceding_char = Some(mapped);
+    }
 
-                // Parse the command number (default to 0 if invalid)
-                let cmd_str = std::str::from_utf8(num_slice).unwrap_or("0");
-                let cmd = cmd_str.parse::<usize>().unwrap_or(0);
-                (cmd, payload_slice.to_vec())
+    fn execute(&mut self, byte: u8, output: &mut Vec<TerminalOutput>) {
+        self.flush_data(output);
+        match byte {
+            0x00 => {}
+            0x05 => {
+                self.replies.extend_from_slice(&self.answerback);
+                self.emit_output(output, TerminalOutput::Enquiry);
             }
-            None => {
-                // Edge case: OSC without a payload or separator
-                // Try to parse the entire buffer as command number
-                let cmd_str = std::str::from_utf8(&self.osc_buffer).unwrap_or("0");
-                let cmd = cmd_str.parse::<usize>().unwrap_or(0);
-                (cmd, self.osc_buffer.clone())
+            0x07 => self.emit_output(output, TerminalOutput::Bell),
+            0x08 | 0x7f => self.emit_output(output, TerminalOutput::Backspace),
+            0x09 => self.emit_output(output, TerminalOutput::HorizontalTab),
+            0x0A => self.line_feed(TerminalOutput::Newline, output),
+            0x0B => self.line_feed(TerminalOutput::VerticalTab, output),
+            0x0C => self.line_feed(TerminalOutput::FormFeed, output),
+            0x0D => self.emit_output(output, TerminalOutput::CarriageReturn),
+            // Shift Out - activate G1
+            0x0E => self.invoke_charset(CharsetIndex::G1, output),
+            // Shift In - activate G0
+            0x0F => self.invoke_charset(CharsetIndex::G0, output),
+            0x11..=0x14 => {
+                self.emit_output(output, TerminalOutput::DeviceControl { code: byte });
             }
-        };
+            // CAN and SUB abort any sequence in progress; SUB also shows an error glyph
+            0x18 => {}
+            0x1A => {
+                let mut buf = [0; 4];
+                self.data_buffer.extend_from_slice(SUBSTITUTE_GLYPH.encode_utf8(&mut buf).as_bytes());
+            }
+            _ => debug!("Unhandled C0 execute: {:02X}", byte),
+        }
+    }
 
-        // Handle specific OSC commands
-        match command {
-            // OSC 8 - Hyperlinks
-            8 => {
-                // Format: OSC 8 ; params ; URI ST
-                // If URI is empty, clear hyperlink
-                if payload.is_empty() {
-                    self.emit_output(output, TerminalOutput::ClearHyperlink);
-                } else {
-                    // Split params and URI
-                    if let Some(semicolon_pos) = payload.iter().position(|&b| b == b';') {
-                        let params_slice = &payload[..semicolon_pos];
-                        let uri_slice = &payload[semicolon_pos + 1..];
-
-                        // Parse ID from params (format: key=value:key=value)
-                        let id = if !params_slice.is_empty() {
-                            std::str::from_utf8(params_slice)
-                                .ok()
-                                .and_then(|s| {
-                                    s.split(':')
-                                        .find_map(|kv| kv.strip_prefix("id="))
-                                        .map(|id| id.to_string())
-                                })
-                        } else {
-                            None
-                        };
-
-                        if let Ok(uri) = std::str::from_utf8(uri_slice) {
-                            if uri.is_empty() {
-                                self.emit_output(output, TerminalOutput::ClearHyperlink);
-                            } else {
-                                self.emit_output(
-                                    output,
-                                    TerminalOutput::SetHyperlink {
-                                        id,
-                                        uri: uri.to_string(),
-                                    },
-                                );
-                            }
-                        }
+    fn set_mode(&mut self, param: usize, private: bool, enable: bool, output: &mut Vec<TerminalOutput>) {
+        let mode = match (private, param) {
+            (true, 25) => return self.emit_output(output, TerminalOutput::SetCursorVisibility(enable)),
+            (true, 1049) if enable => return self.emit_output(output, TerminalOutput::EnterAltScreen),
+            (true, 1049) => return self.emit_output(output, TerminalOutput::ExitAltScreen),
+            (true, 2026) if enable => {
+                output.push(TerminalOutput::BeginSynchronizedUpdate);
+                if !self.untrusted {
+                    self.sync_update_depth += 1;
+                }
+                return;
+            }
+            (true, 2026) => {
+                if self.sync_update_depth > 0 {
+                    self.sync_update_depth -= 1;
+                    if self.sync_update_depth == 0 {
+                        self.flush_sync_buffer(output);
                     }
                 }
+                output.push(TerminalOutput::EndSynchronizedUpdate);
+                return;
             }
-            _ => {
-                // Generic OSC handling
-                self.emit_output(output, TerminalOutput::Osc { command, payload });
+            _ => Mode::from_param(param, private),
+        };
+        match mode {
+            // DECANM: the following input uses the VT52 grammar
+            Mode::Ansi if !enable && !self.untrusted => self.vt52 = Some(Vt52State::default()),
+            Mode::NationalReplacement => self.nrc_mode = enable,
+            Mode::LineFeedNewLine => self.line_feed_new_line = enable,
+            Mode::LeftRightMargin => self.left_right_margin_mode = enable,
+            _ => {}
+        }
+        let item = if enable { TerminalOutput::SetMode(mode) } else { TerminalOutput::ResetMode(mode) };
+        self.emit_output(output, item);
+    }
+
+    /// LF, VT and FF also return the carriage in LNM mode
+    fn line_feed(&mut self, item: TerminalOutput, output: &mut Vec<TerminalOutput>) {
+        self.emit_output(output, item);
+        if self.line_feed_new_line {
+            self.emit_output(output, TerminalOutput::CarriageReturn);
+        }
+    }
+
+    /// Emit a handler's events and queue its reply
+    fn apply_response(&mut self, response: HandlerResponse, output: &mut Vec<TerminalOutput>) {
+        for item in response.events {
+            self.emit_output(output, item);
+        }
+        self.replies.extend(self.control_encoding.convert(&response.reply));
+    }
+
+    fn perform_osc_dispatch(&mut self, terminator: StringTerminator, output: &mut Vec<TerminalOutput>) {
+        if self.osc_buffer.is_empty() {
+            return;
+        }
+
+        // Registered commands (hyperlinks, colours, clipboard, user handlers, ...)
+        let (command, params) = osc::split_command(&self.osc_buffer);
+        if let Some(response) = command.and_then(|command| self.handlers.osc(command, params, terminator)) {
+            self.apply_response(response, output);
+            return;
+        }
+
+        // Generic OSC handling
+        let item = match (command, params) {
+            (Some(command), Some(payload)) => TerminalOutput::Osc { command, payload: payload.to_vec() },
+            // Edge case: OSC without a payload or separator
+            (Some(command), None) => TerminalOutput::Osc { command, payload: self.osc_buffer.clone() },
+            (None, payload) => {
+                let command_len = self.osc_buffer.len() - payload.map_or(0, |p| p.len() + 1);
+                TerminalOutput::NamedOsc {
+                    command: self.osc_buffer[..command_len].to_vec(),
+                    payload: payload.unwrap_or_default().to_vec(),
+                }
             }
+        };
+        self.emit_output(output, item);
+    }
+
+    fn perform_dcs_dispatch(&mut self, output: &mut Vec<TerminalOutput>) {
+        // Take ownership first to avoid multiple mutable borrows
+        let params = std::mem::take(&mut self.dcs_params_cache);
+        let intermediates = std::mem::take(&mut self.dcs_intermediates_cache);
+        let data = std::mem::take(&mut self.dcs_buffer);
+
+        // The final byte is stored at the start of the data
+        let response = data
+            .split_first()
+            .and_then(|(&final_byte, body)| self.handlers.dcs(&intermediates, final_byte, &params, body));
+        if let Some(response) = response {
+            self.apply_response(response, output);
+            return;
         }
+
+        // Emit the full package: Params + Intermediates + Data
+        self.emit_output(output, TerminalOutput::DeviceControlString { params, intermediates, data });
     }
+
+    fn perform_apc_dispatch(&mut self, output: &mut Vec<TerminalOutput>) {
+        match self.handlers.apc(&self.osc_buffer) {
+            Some(response) => self.apply_response(response, output),
+            None => debug!("Ignoring APC string of {} bytes", self.osc_buffer.len()),
+        }
+    }
+
     fn perform_esc_dispatch(&mut self, terminator: u8, output: &mut Vec<TerminalOutput>) {
         match (self.intermediates.first(), terminator) {
-            (None, b'D') => self.emit_output(output, TerminalOutput::Newline),
-            (None, b'M') => self.emit_output(output, TerminalOutput::CursorUp(1)),
-            (None, b'E') => self.emit_output(output, TerminalOutput::Newline),
+            (None, b'D') => self.emit_output(output, TerminalOutput::Index),
+            (None, b'M') => self.emit_output(output, TerminalOutput::ReverseIndex),
+            (None, b'E') => self.emit_output(output, TerminalOutput::NextLine),
+            (None, b'7') => self.emit_output(output, TerminalOutput::SaveCursor),
+            (None, b'8') => self.emit_output(output, TerminalOutput::RestoreCursor),
+            (None, b'c') => {
+                self.reset_state(output);
+                output.push(TerminalOutput::FullReset);
+            }
+            (None, b'V') => self.emit_output(output, TerminalOutput::StartGuardedArea),
+            (None, b'W') => self.emit_output(output, TerminalOutput::EndGuardedArea),
+            (None, b'=') => self.emit_output(output, TerminalOutput::SetMode(Mode::KeypadApplication)),
+            (None, b'>') => self.emit_output(output, TerminalOutput::ResetMode(Mode::KeypadApplication)),
             // Explicitly handle String Terminator (ST, ESC \)
             (None, b'\\') => { /* Handled in OscEnd or Unhook actions */
                 warn!("Unexpected String Terminator ESC \\ outside of string sequence");
             }
-            // Charset designation
-            (Some(&b'('), charset) => {
-                // Designate G0
-                if let Some(cs) = self.parse_charset(charset) {
-                    self.charsets[0] = cs;
-                    self.emit_output(
-                        output,
-                        TerminalOutput::ConfigureCharset {
-                            index: CharsetIndex::G0,
-                            charset: cs,
-                        },
-                    );
+            (Some(&b'%'), b'G' | b'@') => {
+                let encoding = if terminator == b'G' { InputEncoding::Utf8 } else { InputEncoding::SingleByte };
+                if !self.untrusted {
+                    self.set_input_encoding(encoding);
                 }
+                self.emit_output(output, TerminalOutput::SetInputEncoding(encoding));
             }
-            (Some(&b')'), charset) => {
-                // Designate G1
-                if let Some(cs) = self.parse_charset(charset) {
-                    self.charsets[1] = cs;
-                    self.emit_output(
-                        output,
-                        TerminalOutput::ConfigureCharset {
-                            index: CharsetIndex::G1,
-                            charset: cs,
-                        },
-                    );
-                }
-            }
-            (Some(&b'*'), charset) => {
-                // Designate G2
-                if let Some(cs) = self.parse_charset(charset) {
-                    self.charsets[2] = cs;
-                    self.emit_output(
-                        output,
-                        TerminalOutput::ConfigureCharset {
-                            index: CharsetIndex::G2,
-                            charset: cs,
-                        },
-                    );
-                }
+            (Some(&b' '), b'F' | b'G') => {
+                self.control_encoding =
+                    if terminator == b'G' { ControlEncoding::EightBit } else { ControlEncoding::SevenBit };
+                self.emit_output(output, TerminalOutput::SetControlEncoding(self.control_encoding));
             }
-            (Some(&b'+'), charset) => {
-                // Designate G3
-                if let Some(cs) = self.parse_charset(charset) {
-                    self.charsets[3] = cs;
-                    self.emit_output(
-                        output,
-                        TerminalOutput::ConfigureCharset {
-                            index: CharsetIndex::G3,
-                            charset: cs,
-                        },
-                    );
-                }
+            // Charset designation
+            (Some(&designator @ (b'(' | b')' | b'*' | b'+' | b'-' | b'.' | b'/')), final_byte) => {
+                self.designate_charset(designator, final_byte, output)
             }
+            // Locking shifts into GL and GR, single shifts
+            (None, b'n') => self.invoke_charset(CharsetIndex::G2, output),
+            (None, b'o') => self.invoke_charset(CharsetIndex::G3, output),
+            (None, b'~') => self.invoke_gr_charset(CharsetIndex::G1, output),
+            (None, b'}') => self.invoke_gr_charset(CharsetIndex::G2, output),
+            (None, b'|') => self.invoke_gr_charset(CharsetIndex::G3, output),
+            (None, b'N') => self.single_shift(CharsetIndex::G2, output),
+            (None, b'O') => self.single_shift(CharsetIndex::G3, output),
             (Some(&b'#'), terminator) => {
                 match terminator {
                     b'3' => {
@@ -436,6 +808,11 @@ impl AnsiParser {
                         debug!("DECDWL Double Width Line");
                         self.emit_output(output, TerminalOutput::SetDoubleWidthLine);
                     }
+                    b'8' => {
+                        // ESC # 8 - DECALN Screen Alignment Test
+                        debug!("DECALN Screen Alignment Test");
+                        self.emit_output(output, TerminalOutput::ScreenAlignmentTest);
+                    }
                     _ => warn!("Unknown DEC line drawing sequence: ESC # {}", terminator as char),
                 }
             }
@@ -444,65 +821,83 @@ impl AnsiParser {
         }
     }
 
-    fn parse_charset(&self, byte: u8) -> Option<StandardCharset> {
-        match byte {
-            b'B' => Some(StandardCharset::Ascii),
-            b'0' => Some(StandardCharset::SpecialCharacterAndLineDrawing),
-            _ => None,
+    fn designate_charset(&mut self, designator: u8, final_byte: u8, output: &mut Vec<TerminalOutput>) {
+        let (index, is_96) = match designator {
+            b'(' => (CharsetIndex::G0, false),
+            b')' => (CharsetIndex::G1, false),
+            b'*' => (CharsetIndex::G2, false),
+            b'+' => (CharsetIndex::G3, false),
+            b'-' => (CharsetIndex::G1, true),
+            b'.' => (CharsetIndex::G2, true),
+            _ => (CharsetIndex::G3, true),
+        };
+        let charset = if is_96 {
+            StandardCharset::from_96_designator(&self.intermediates[1..], final_byte)
+        } else {
+            StandardCharset::from_designator(&self.intermediates[1..], final_byte)
+        };
+        match charset {
+            Some(charset) if charset.is_national() && !self.nrc_mode => {
+                debug!("Ignoring national charset {:?} outside DECNRCM mode", charset)
+            }
+            Some(charset) => {
+                self.charsets[index as usize] = charset;
+                self.emit_output(output, TerminalOutput::ConfigureCharset { index, charset });
+            }
+            None => warn!("Unknown charset designation: {:?} {}", self.intermediates, final_byte as char),
         }
     }
 
+    /// Locking shift into GL
+    fn invoke_charset(&mut self, index: CharsetIndex, output: &mut Vec<TerminalOutput>) {
+        self.active_charset = index;
+        self.emit_output(output, TerminalOutput::SetActiveCharset(index));
+    }
+
+    /// Locking shift into GR
+    fn invoke_gr_charset(&mut self, index: CharsetIndex, output: &mut Vec<TerminalOutput>) {
+        self.active_gr_charset = index;
+        self.emit_output(output, TerminalOutput::SetActiveGrCharset(index));
+    }
+
+    fn single_shift(&mut self, index: CharsetIndex, output: &mut Vec<TerminalOutput>) {
+        self.single_shift = Some(index);
+        self.emit_output(output, TerminalOutput::SingleShift(index));
+    }
+
     fn perform_csi_dispatch(&mut self, terminator: u8, output: &mut Vec<TerminalOutput>) {
 
         let has_question_mark = self.intermediates.first() == Some(&b'?');
         let is_empty_intermediates = self.intermediates.is_empty();
         let param = self.get_param(0, 0);
 
-        // Check for private mode sequences.
-        if has_question_mark || (is_empty_intermediates && (terminator == b'h' || terminator == b'l')) {
-            match terminator {
-                b'h' => match param {
-                    4 => self.emit_output(output, TerminalOutput::SetMode(Mode::Insert)),
-                    20 => self.emit_output(output, TerminalOutput::SetMode(Mode::LineFeedNewLine)),
-                    25 => self.emit_output(output, TerminalOutput::SetCursorVisibility(true)),
-                    1049 => self.emit_output(output, TerminalOutput::EnterAltScreen),
-                    1 => self.emit_output(output, TerminalOutput::SetMode(Mode::Decckm)),
-                    2026 => {
-                        output.push(TerminalOutput::BeginSynchronizedUpdate);
-                        self.sync_update_depth += 1;
-                    }
-
-                    2004 => self.emit_output(output, TerminalOutput::SetMode(Mode::BracketedPaste)),
-                    1037 => self.emit_output(output, TerminalOutput::SetMode(Mode::ModifyOtherKeys)),
-                    _ => {}
-                },
-                b'l' => match param {
-                    4 => self.emit_output(output, TerminalOutput::ResetMode(Mode::Insert)),
-                    20 => self.emit_output(output, TerminalOutput::ResetMode(Mode::LineFeedNewLine)),
-                    25 => self.emit_output(output, TerminalOutput::SetCursorVisibility(false)),
-                    1049 => self.emit_output(output, TerminalOutput::ExitAltScreen),
-                    1 => self.emit_output(output, TerminalOutput::ResetMode(Mode::Decckm)),
-                    2026 => {
-                        if self.sync_update_depth > 0 {
-                            self.sync_update_depth -= 1;
-                            if self.sync_update_depth == 0 {
-                                self.flush_sync_buffer(output);
-                            }
-                        }
-                        output.push(TerminalOutput::EndSynchronizedUpdate);
-                    }
-                    2004 => self.emit_output(output, TerminalOutput::ResetMode(Mode::BracketedPaste)),
-                    1037 => self.emit_output(output, TerminalOutput::ResetMode(Mode::ModifyOtherKeys)),
-                    _ => {}
-                },
-                _ => {}
+        // SM/RM and DECSET/DECRST, each parameter applied in turn
+        if matches!(terminator, b'h' | b'l') && (is_empty_intermediates || self.intermediates == b"?") {
+            let params = if self.params.is_empty() { vec![0] } else { self.params.clone() };
+            for param in params {
+                self.set_mode(param, has_question_mark, terminator == b'h', output);
             }
-            if has_question_mark || param == 1049 || param == 25 || param == 1 || param == 2026 {
-                return;
+            return;
+        }
+        if has_question_mark {
+            // DECSED and DECSEL
+            if self.intermediates == b"?" && matches!(terminator, b'J' | b'K') {
+                let range = match param {
+                    0 => EraseRange::Forwards,
+                    1 => EraseRange::Backwards,
+                    2 => EraseRange::All,
+                    _ => return,
+                };
+                let item = if terminator == b'J' {
+                    TerminalOutput::SelectiveEraseDisplay(range)
+                } else {
+                    TerminalOutput::SelectiveEraseLine(range)
+                };
+                self.emit_output(output, item);
             }
+            return;
         }
 
-
         let has_space_intermediate = self.intermediates.first() == Some(&b' ');
         let intermediates_empty = self.intermediates.is_empty();
 
@@ -531,7 +926,7 @@ impl AnsiParser {
             (_, true, b'b') => {
                 if let Some(ch) = self.preceding_char {
                     let count = self.get_param(0, 1);
-                    let repeated: String = std::iter::repeat(ch).take(count).collect();
+                    let repeated: String = std::iter::repeat_n(ch, count).collect();
                     self.emit_output(output, TerminalOutput::Data(repeated.into_bytes()));
                 }
             }
@@ -558,6 +953,13 @@ impl AnsiParser {
                     y: None,
                 },
             ),
+            (_, true, b'd') => self.emit_output(
+                output,
+                TerminalOutput::SetCursorPos {
+                    x: None,
+                    y: Some(self.get_param(0, 1).max(1)),
+                },
+            ),
 
             // Erasing
             (_, true, b'J') => match self.get_param(0, 0) {
@@ -575,16 +977,59 @@ impl AnsiParser {
             (_, true, b'@') => self.emit_output(output, TerminalOutput::InsertSpaces(self.get_param(0, 1))),
             // Graphics (SGR)
             (_, true, b'm') => self.parse_sgr(output),
+            // Window manipulation
+            (_, true, b't') => match (param, TitleTarget::from_param(self.get_param(1, 0))) {
+                (22, Some(target)) => self.emit_output(output, TerminalOutput::PushTitle(target)),
+                (23, Some(target)) => self.emit_output(output, TerminalOutput::PopTitle(target)),
+                _ => match parse_window_op(&self.params) {
+                    Some(op) if (self.window_op_policy)(&op) => {
+                        self.emit_output(output, TerminalOutput::WindowOp(op))
+                    }
+                    Some(op) => warn!("Window operation {:?} denied by policy", op),
+                    None => warn!("Unsupported window operation: params={:?}", self.params),
+                },
+            },
+            // Rectangular area operations
+            (false, false, b'v' | b'x' | b'z' | b'{' | b'r' | b't') if self.intermediates == b"$" => {
+                self.perform_rectangle_dispatch(terminator, output)
+            }
+            // DECSTR
+            (false, false, b'p') if self.intermediates == b"!" => {
+                self.reset_charsets();
+                self.emit_output(output, TerminalOutput::SoftReset);
+            }
+            // DECSCA
+            (false, false, b'q') if self.intermediates == b"\"" => match param {
+                0 | 2 => self.emit_output(output, TerminalOutput::SetCharacterProtection(false)),
+                1 => self.emit_output(output, TerminalOutput::SetCharacterProtection(true)),
+                _ => {}
+            },
+            // DECRQCRA
+            (false, false, b'y') if self.intermediates == b"*" => {
+                let id = self.get_param(0, 0);
+                let page = self.get_param(1, 1).max(1);
+                let area = Rect::from_params(self.params.get(2..).unwrap_or_default());
+                self.emit_output(output, TerminalOutput::RequestChecksum { id, page, area });
+            }
+            // Title modes
+            (false, false, b't' | b'T') if self.intermediates == b">" => {
+                let mut title_modes = self.title_modes.lock().unwrap_or_else(PoisonError::into_inner);
+                title_modes.apply(&self.params, terminator == b't');
+            }
             // Scrolling region
             (_, true, b'r') => {
-                let top = self.get_param(0, 1);  // Default to 1 if not provided
-                let bottom = self.get_param_opt(1);
-
-                // If bottom is not provided, use the terminal height
-                let bottom = bottom.unwrap_or(0); // 0 means "use default"
-
-                self.emit_output(output, TerminalOutput::SetScrollingRegion { top, bottom:Some(bottom) });
+                let top = self.get_margin(0);
+                let bottom = self.get_margin(1);
+                self.emit_output(output, TerminalOutput::SetScrollingRegion { top, bottom });
             }
+            // DECSLRM shadows SCOSC while left/right margins are enabled
+            (_, true, b's') if self.left_right_margin_mode => {
+                let left = self.get_margin(0);
+                let right = self.get_margin(1);
+                self.emit_output(output, TerminalOutput::SetLeftRightMargins { left, right });
+            }
+            (_, true, b's') => self.emit_output(output, TerminalOutput::SaveCursor),
+            (_, true, b'u') => self.emit_output(output, TerminalOutput::RestoreCursor),
             _ => {
                 warn!("Unknown CSI: params={:?}, intermediates={:?}, terminator={}",
           self.params, self.intermediates, terminator as char);
@@ -592,6 +1037,54 @@ impl AnsiParser {
         }
     }
 
+    fn perform_rectangle_dispatch(&mut self, terminator: u8, output: &mut Vec<TerminalOutput>) {
+        use SelectGraphicRendition::*;
+        let area = Rect::from_params(&self.params);
+        let item = match terminator {
+            b'v' => TerminalOutput::CopyRectangle {
+                source: area,
+                top: self.get_param(5, 1).max(1),
+                left: self.get_param(6, 1).max(1),
+            },
+            b'x' => {
+                let ch = self.get_param(0, 0);
+                let area = Rect::from_params(self.params.get(1..).unwrap_or_default());
+                match u8::try_from(ch) {
+                    Ok(ch @ (32..=126 | 160..=255)) => TerminalOutput::FillRectangle { ch: char::from(ch), area },
+                    _ => return warn!("DECFRA with invalid character {}", ch),
+                }
+            }
+            b'z' => TerminalOutput::EraseRectangle(area),
+            b'{' => TerminalOutput::SelectiveEraseRectangle(area),
+            _ => {
+                let attributes = match self.params.get(4..).filter(|params| !params.is_empty()) {
+                    Some(params) => params.iter().map(|&param| SelectGraphicRendition::from_usize(param)).collect(),
+                    None => vec![Reset],
+                };
+                if terminator == b'r' {
+                    let attributes = attributes
+                        .into_iter()
+                        .filter(|sgr| {
+                            matches!(
+                                sgr,
+                                Reset | Bold | Underline | BlinkSlow | Reverse | NormalIntensity | NotUnderline
+                                    | Unknown(25 | 27)
+                            )
+                        })
+                        .collect();
+                    TerminalOutput::ChangeRectangleAttributes { area, attributes }
+                } else {
+                    let attributes = attributes
+                        .into_iter()
+                        .filter(|sgr| matches!(sgr, Reset | Bold | Underline | BlinkSlow | Reverse))
+                        .collect();
+                    TerminalOutput::ReverseRectangleAttributes { area, attributes }
+                }
+            }
+        };
+        self.emit_output(output, item);
+    }
+
     fn parse_sgr(&mut self, output: &mut Vec<TerminalOutput>) {
         if self.params.is_empty() {
             self.emit_output(output, TerminalOutput::Sgr(SelectGraphicRendition::Reset));
@@ -660,20 +1153,4 @@ impl AnsiParser {
             i += 1;
         }
     }
-
-    // Helper that bypasses sync buffer check (for internal use during sync processing)
-    fn emit_output_unchecked(&self, output: &mut Vec<TerminalOutput>, item: TerminalOutput) {
-        if self.sync_update_depth > 0 {
-            // This is a bit of a hack - we need mutable access but we're in an immutable context
-            // In practice, this is only called from parse_sgr which is called with mutable self
-            // but the borrow checker doesn't see through it
-            // For now, we'll just push directly
-            unsafe {
-                let self_mut = self as *const Self as *mut Self;
-                (*self_mut).sync_buffer.push(item);
-            }
-        } else {
-            output.push(item);
-        }
-    }
 }
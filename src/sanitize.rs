@@ -0,0 +1,190 @@
+use crate::definitions::{CharsetIndex, SelectGraphicRendition, TerminalOutput};
+use crate::parser::AnsiParser;
+
+/// Which categories of terminal output survive sanitisation. Everything not
+/// listed here (titles, clipboard access, cursor movement, mode changes,
+/// device control strings, ...) is always dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizePolicy {
+    /// Keep colours and text attributes
+    pub sgr: bool,
+    /// Keep OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    /// URI schemes accepted for hyperlinks (case-insensitive). Empty accepts any scheme.
+    pub hyperlink_schemes: Vec<String>,
+    /// Keep LF, CR, BS and HT
+    pub line_controls: bool,
+    /// Replace stripped control characters with their Unicode control pictures (e.g. `␇`)
+    pub placeholders: bool,
+}
+
+impl Default for SanitizePolicy {
+    /// SGR, http(s) hyperlinks and line controls
+    fn default() -> Self {
+        SanitizePolicy {
+            sgr: true,
+            hyperlinks: true,
+            hyperlink_schemes: vec!["http".to_string(), "https".to_string()],
+            line_controls: true,
+            placeholders: false,
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// Plain text only: every escape sequence and control character is removed
+    pub fn text_only() -> SanitizePolicy {
+        SanitizePolicy {
+            sgr: false,
+            hyperlinks: false,
+            hyperlink_schemes: Vec::new(),
+            line_controls: true,
+            placeholders: false,
+        }
+    }
+
+    fn allows_uri(&self, uri: &str) -> bool {
+        if uri.bytes().any(|b| b < 0x20 || b == 0x7f) {
+            return false;
+        }
+        if self.hyperlink_schemes.is_empty() {
+            return true;
+        }
+        match uri.split_once(':') {
+            Some((scheme, _)) => self
+                .hyperlink_schemes
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme)),
+            None => false,
+        }
+    }
+}
+
+/// Filters untrusted output down to the categories allowed by a [`SanitizePolicy`],
+/// re-encoding what is kept as canonical escape sequences.
+pub struct Sanitizer {
+    pub(crate) parser: AnsiParser,
+    policy: SanitizePolicy,
+    // Whether we emitted an OSC 8 open that still needs its matching close
+    link_open: bool,
+}
+
+impl Sanitizer {
+    pub fn new(policy: SanitizePolicy) -> Sanitizer {
+        let mut parser = AnsiParser::new();
+        parser.set_untrusted(true);
+        Sanitizer {
+            parser,
+            policy,
+            link_open: false,
+        }
+    }
+
+    pub fn policy(&self) -> &SanitizePolicy {
+        &self.policy
+    }
+
+    /// Sanitise a chunk of input. Sequences split across chunks are handled
+    /// like they are by [`AnsiParser::push`].
+    pub fn push(&mut self, incoming: &[u8]) -> Vec<u8> {
+        let mut clean = Vec::with_capacity(incoming.len());
+        for item in self.parser.push(incoming) {
+            self.filter(item, &mut clean);
+        }
+        // Nothing is ever sent back to the source
+        self.parser.take_replies();
+        clean
+    }
+
+    fn filter(&mut self, item: TerminalOutput, clean: &mut Vec<u8>) {
+        match item {
+            TerminalOutput::Data(bytes) => self.push_text(&bytes, clean),
+            // Malformed extended colours can't be re-emitted faithfully
+            TerminalOutput::Sgr(sgr)
+                if self.policy.sgr && !matches!(sgr, SelectGraphicRendition::Unknown(38 | 48)) =>
+            {
+                clean.extend_from_slice(&sgr.encode());
+            }
+            TerminalOutput::SetHyperlink { id, uri } => self.set_link(id.as_deref(), &uri, clean),
+            TerminalOutput::ClearHyperlink => self.clear_link(clean),
+            TerminalOutput::Newline | TerminalOutput::Index => self.push_control(0x0A, clean),
+            TerminalOutput::VerticalTab => self.push_control(0x0B, clean),
+            TerminalOutput::FormFeed => self.push_control(0x0C, clean),
+            TerminalOutput::NextLine => {
+                self.push_control(0x0D, clean);
+                self.push_control(0x0A, clean);
+            }
+            TerminalOutput::CarriageReturn => self.push_control(0x0D, clean),
+            TerminalOutput::Backspace => self.push_control(0x08, clean),
+            TerminalOutput::HorizontalTab => self.push_control(0x09, clean),
+            TerminalOutput::Bell => self.push_placeholder(0x07, clean),
+            TerminalOutput::Enquiry => self.push_placeholder(0x05, clean),
+            TerminalOutput::DeviceControl { code } => self.push_placeholder(code, clean),
+            TerminalOutput::SetActiveCharset(CharsetIndex::G0) => self.push_placeholder(0x0F, clean),
+            TerminalOutput::SetActiveCharset(CharsetIndex::G1) => self.push_placeholder(0x0E, clean),
+            _ => {}
+        }
+    }
+
+    fn push_text(&self, bytes: &[u8], clean: &mut Vec<u8>) {
+        // Data is re-validated so stray C1 controls and invalid UTF-8 can't sneak through
+        let mut buf = [0; 4];
+        for c in String::from_utf8_lossy(bytes).chars() {
+            if c.is_control() {
+                if self.policy.placeholders {
+                    clean.extend_from_slice('\u{FFFD}'.encode_utf8(&mut buf).as_bytes());
+                }
+            } else {
+                clean.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+
+    fn push_control(&self, code: u8, clean: &mut Vec<u8>) {
+        if self.policy.line_controls {
+            clean.push(code);
+        } else {
+            self.push_placeholder(code, clean);
+        }
+    }
+
+    fn push_placeholder(&self, code: u8, clean: &mut Vec<u8>) {
+        if !self.policy.placeholders {
+            return;
+        }
+        // Control Pictures block: U+2400 + code, with U+2421 for DEL
+        let picture = match code {
+            0x00..=0x1F => char::from_u32(0x2400 + code as u32),
+            0x7F => Some('\u{2421}'),
+            _ => None,
+        };
+        if let Some(picture) = picture {
+            let mut buf = [0; 4];
+            clean.extend_from_slice(picture.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+
+    fn set_link(&mut self, id: Option<&str>, uri: &str, clean: &mut Vec<u8>) {
+        if !self.policy.hyperlinks {
+            return;
+        }
+        if !self.policy.allows_uri(uri) {
+            // A rejected link still ends the previous one
+            self.clear_link(clean);
+            return;
+        }
+        let id = id
+            .filter(|id| !id.bytes().any(|b| b < 0x20 || b == b';' || b == b':'))
+            .map(str::to_string);
+        let link = TerminalOutput::SetHyperlink { id, uri: uri.to_string() };
+        clean.extend_from_slice(&link.encode());
+        self.link_open = true;
+    }
+
+    fn clear_link(&mut self, clean: &mut Vec<u8>) {
+        if self.link_open {
+            clean.extend_from_slice(&TerminalOutput::ClearHyperlink.encode());
+            self.link_open = false;
+        }
+    }
+}
@@ -0,0 +1,661 @@
+use std::ops::RangeInclusive;
+
+use crate::definitions::{EraseRange, Mode, Rect, SelectGraphicRendition, TerminalOutput};
+use crate::export::{is_background, is_foreground};
+
+/// Rendition of a cell, as set by SGR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub fg: SelectGraphicRendition,
+    pub bg: SelectGraphicRendition,
+    pub bold: bool,
+    pub faint: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub blink: bool,
+    pub reverse: bool,
+    pub conceal: bool,
+    /// Set by DECSCA or SPA; SGR leaves it alone
+    pub protected: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            fg: SelectGraphicRendition::ForegroundDefault,
+            bg: SelectGraphicRendition::BackgroundDefault,
+            bold: false,
+            faint: false,
+            italic: false,
+            underline: false,
+            blink: false,
+            reverse: false,
+            conceal: false,
+            protected: false,
+        }
+    }
+}
+
+impl Style {
+    pub fn apply(&mut self, sgr: SelectGraphicRendition) {
+        use SelectGraphicRendition::*;
+        match sgr {
+            Reset => *self = Style { protected: self.protected, ..Style::default() },
+            Bold => self.bold = true,
+            Faint => self.faint = true,
+            NormalIntensity => {
+                self.bold = false;
+                self.faint = false;
+            }
+            Italic => self.italic = true,
+            NotItalic => self.italic = false,
+            Underline => self.underline = true,
+            NotUnderline => self.underline = false,
+            BlinkSlow | BlinkRapid => self.blink = true,
+            Unknown(25) => self.blink = false,
+            Reverse => self.reverse = true,
+            Unknown(27) => self.reverse = false,
+            Conceal => self.conceal = true,
+            Reveal => self.conceal = false,
+            sgr if is_foreground(sgr) => self.fg = sgr,
+            sgr if is_background(sgr) => self.bg = sgr,
+            _ => {}
+        }
+    }
+
+    /// DECCARA: only bold, underline, blink and reverse can be changed
+    fn change_attribute(&mut self, sgr: SelectGraphicRendition) {
+        use SelectGraphicRendition::*;
+        match sgr {
+            Reset => {
+                self.bold = false;
+                self.underline = false;
+                self.blink = false;
+                self.reverse = false;
+            }
+            Bold | NormalIntensity | Underline | NotUnderline | BlinkSlow | Unknown(25) | Reverse | Unknown(27) => {
+                self.apply(sgr)
+            }
+            _ => {}
+        }
+    }
+
+    /// DECRARA
+    fn reverse_attribute(&mut self, sgr: SelectGraphicRendition) {
+        use SelectGraphicRendition::*;
+        match sgr {
+            Reset => {
+                self.bold = !self.bold;
+                self.underline = !self.underline;
+                self.blink = !self.blink;
+                self.reverse = !self.reverse;
+            }
+            Bold => self.bold = !self.bold,
+            Underline => self.underline = !self.underline,
+            BlinkSlow => self.blink = !self.blink,
+            Reverse => self.reverse = !self.reverse,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub c: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell { c: ' ', style: Style::default() }
+    }
+}
+
+/// Size attribute of a line, set by DECDWL, DECDHL and DECSWL. Double-size
+/// lines hold half as many characters; renderers draw each cell twice as wide,
+/// and for double height only the given half of the glyphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineSize {
+    #[default]
+    SingleWidth,
+    DoubleWidth,
+    DoubleHeightTop,
+    DoubleHeightBottom,
+}
+
+/// Cursor state kept by DECSC/SCOSC
+#[derive(Debug, Clone, Copy, Default)]
+struct SavedCursor {
+    x: usize,
+    y: usize,
+    style: Style,
+    origin_mode: bool,
+}
+
+/// A fixed-size grid that replays parser output: printing with autowrap,
+/// cursor movement, scrolling margins, erasing and rectangular area
+/// operations. Every character takes one cell; there is no scrollback.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    columns: usize,
+    rows: usize,
+    lines: Vec<Vec<Cell>>,
+    line_sizes: Vec<LineSize>,
+    // Cursor, 0-based, and whether the next character wraps first
+    x: usize,
+    y: usize,
+    pending_wrap: bool,
+    style: Style,
+    origin_mode: bool,
+    auto_wrap: bool,
+    left_right_margin_mode: bool,
+    // Whether protection came from SPA, which also guards against ED and EL
+    iso_protection: bool,
+    // Margins, 0-based and inclusive; left/right only apply in DECLRMM mode
+    top: usize,
+    bottom: usize,
+    left: usize,
+    right: usize,
+    saved: SavedCursor,
+}
+
+impl Screen {
+    pub fn new(columns: usize, rows: usize) -> Screen {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        Screen {
+            columns,
+            rows,
+            lines: vec![vec![Cell::default(); columns]; rows],
+            line_sizes: vec![LineSize::default(); rows],
+            x: 0,
+            y: 0,
+            pending_wrap: false,
+            style: Style::default(),
+            origin_mode: false,
+            auto_wrap: true,
+            left_right_margin_mode: false,
+            iso_protection: false,
+            top: 0,
+            bottom: rows - 1,
+            left: 0,
+            right: columns - 1,
+            saved: SavedCursor::default(),
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Cursor position as 0-based `(column, row)`
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Rendition used for the next characters
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    pub fn cell(&self, column: usize, row: usize) -> Option<&Cell> {
+        self.lines.get(row)?.get(column)
+    }
+
+    pub fn line_size(&self, row: usize) -> LineSize {
+        self.line_sizes.get(row).copied().unwrap_or_default()
+    }
+
+    /// Number of characters a line holds: half the columns on double-size lines
+    pub fn line_width(&self, row: usize) -> usize {
+        match self.line_size(row) {
+            LineSize::SingleWidth => self.columns,
+            _ => (self.columns / 2).max(1),
+        }
+    }
+
+    /// The characters of a line, without trailing blanks
+    pub fn line_text(&self, row: usize) -> String {
+        let text: String = self.lines.get(row).into_iter().flatten().map(|cell| cell.c).collect();
+        text.trim_end().to_string()
+    }
+
+    /// RIS: everything returns to its initial state, as for a new screen of the
+    /// same size:
+    ///
+    /// - all cells are erased, including protected ones
+    /// - the cursor moves home and the rendition, protection included, is normal
+    /// - scrolling and left/right margins cover the whole screen
+    /// - origin mode and DECLRMM are off, autowrap is on
+    /// - the saved cursor is forgotten
+    pub fn full_reset(&mut self) {
+        *self = Screen::new(self.columns, self.rows);
+    }
+
+    /// DECSTR, following the VT510 table. Restores:
+    ///
+    /// - origin mode (off) and autowrap (off)
+    /// - scrolling and left/right margins, to the whole screen
+    /// - the rendition, including DECSCA protection and ISO guarded areas, to normal
+    /// - the saved cursor, to the home position with normal rendition
+    ///
+    /// Screen contents, the cursor position and DECLRMM are left alone.
+    pub fn soft_reset(&mut self) {
+        self.origin_mode = false;
+        self.auto_wrap = false;
+        self.pending_wrap = false;
+        self.top = 0;
+        self.bottom = self.rows - 1;
+        self.left = 0;
+        self.right = self.columns - 1;
+        self.style = Style::default();
+        self.iso_protection = false;
+        self.saved = SavedCursor::default();
+    }
+
+    /// DECRQCRA checksum of `area`, computed like xterm: the negated 16-bit sum
+    /// of the characters, with flag values added for bold (0x80), blink (0x40),
+    /// reverse (0x20), underline (0x10), concealed (0x08) and protected (0x04)
+    /// cells. The area is addressed like the other rectangle operations.
+    pub fn checksum(&self, area: &Rect) -> u16 {
+        let Some((rows, columns)) = self.resolve(area) else {
+            return 0;
+        };
+        let mut total: u32 = 0;
+        for row in rows {
+            for cell in &self.lines[row][columns.clone()] {
+                let style = cell.style;
+                total = total
+                    .wrapping_add(cell.c as u32)
+                    .wrapping_add(if style.bold { 0x80 } else { 0 })
+                    .wrapping_add(if style.blink { 0x40 } else { 0 })
+                    .wrapping_add(if style.reverse { 0x20 } else { 0 })
+                    .wrapping_add(if style.underline { 0x10 } else { 0 })
+                    .wrapping_add(if style.conceal { 0x08 } else { 0 })
+                    .wrapping_add(if style.protected { 0x04 } else { 0 });
+            }
+        }
+        (total as u16).wrapping_neg()
+    }
+
+    /// Reply to DECRQCRA: `DCS Pi ! ~ xxxx ST`
+    pub fn checksum_reply(&self, id: usize, area: &Rect) -> Vec<u8> {
+        format!("\x1BP{}!~{:04X}\x1B\\", id, self.checksum(area)).into_bytes()
+    }
+
+    /// Update from one parser output
+    pub fn apply(&mut self, output: &TerminalOutput) {
+        match output {
+            TerminalOutput::Data(bytes) => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    self.print(c);
+                }
+            }
+            TerminalOutput::CarriageReturn => self.carriage_return(),
+            TerminalOutput::Newline | TerminalOutput::VerticalTab | TerminalOutput::FormFeed | TerminalOutput::Index => {
+                self.line_feed()
+            }
+            TerminalOutput::NextLine => {
+                self.carriage_return();
+                self.line_feed();
+            }
+            TerminalOutput::ReverseIndex => self.reverse_index(),
+            TerminalOutput::Backspace => self.move_to(self.x.saturating_sub(1), self.y),
+            // Tab stops every 8 columns, stopping at the right margin
+            TerminalOutput::HorizontalTab => {
+                let limit = if self.x <= self.margin_right() { self.margin_right() } else { self.columns - 1 };
+                self.move_to(((self.x / 8 + 1) * 8).min(limit), self.y);
+            }
+            TerminalOutput::CursorUp(n) => {
+                let limit = if self.y >= self.top { self.top } else { 0 };
+                self.move_to(self.x, self.y.saturating_sub((*n).max(1)).max(limit));
+            }
+            TerminalOutput::CursorDown(n) => {
+                let limit = if self.y <= self.bottom { self.bottom } else { self.rows - 1 };
+                self.move_to(self.x, self.y.saturating_add((*n).max(1)).min(limit));
+            }
+            TerminalOutput::CursorForward(n) => {
+                let limit = if self.x <= self.margin_right() { self.margin_right() } else { self.columns - 1 };
+                self.move_to(self.x.saturating_add((*n).max(1)).min(limit), self.y);
+            }
+            TerminalOutput::CursorBackward(n) => {
+                let limit = if self.x >= self.margin_left() { self.margin_left() } else { 0 };
+                self.move_to(self.x.saturating_sub((*n).max(1)).max(limit), self.y);
+            }
+            TerminalOutput::SetCursorPos { x, y } => {
+                let (top, left, bottom, right) = self.bounds();
+                let column = x.map_or(self.x, |x| left.saturating_add(x.saturating_sub(1)).min(right));
+                let row = y.map_or(self.y, |y| top.saturating_add(y.saturating_sub(1)).min(bottom));
+                self.move_to(column, row);
+            }
+            TerminalOutput::ClearForwards => self.erase_display(EraseRange::Forwards, false),
+            TerminalOutput::ClearAll => self.erase_display(EraseRange::All, false),
+            TerminalOutput::ClearLineForwards => self.erase_in_line(EraseRange::Forwards, false),
+            TerminalOutput::SelectiveEraseDisplay(range) => self.erase_display(*range, true),
+            TerminalOutput::SelectiveEraseLine(range) => self.erase_in_line(*range, true),
+            TerminalOutput::SetCharacterProtection(protected) => {
+                self.style.protected = *protected;
+                if *protected {
+                    self.iso_protection = false;
+                }
+            }
+            TerminalOutput::StartGuardedArea => {
+                self.style.protected = true;
+                self.iso_protection = true;
+            }
+            TerminalOutput::EndGuardedArea => self.style.protected = false,
+            TerminalOutput::Delete(n) => self.delete_characters(*n),
+            TerminalOutput::InsertSpaces(n) => self.insert_blanks(*n),
+            TerminalOutput::Sgr(sgr) => self.style.apply(*sgr),
+            TerminalOutput::SetMode(mode) => self.set_mode(mode, true),
+            TerminalOutput::ResetMode(mode) => self.set_mode(mode, false),
+            TerminalOutput::SetScrollingRegion { top, bottom } => {
+                // A zero parameter means the default, as for omitted ones
+                let top = top.unwrap_or(1).saturating_sub(1);
+                let bottom = bottom.filter(|&b| b != 0).unwrap_or(self.rows).min(self.rows).saturating_sub(1);
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.home();
+                }
+            }
+            TerminalOutput::SetLeftRightMargins { left, right } if self.left_right_margin_mode => {
+                let left = left.unwrap_or(1).saturating_sub(1);
+                let right = right.filter(|&r| r != 0).unwrap_or(self.columns).min(self.columns).saturating_sub(1);
+                if left < right {
+                    self.left = left;
+                    self.right = right;
+                    self.home();
+                }
+            }
+            TerminalOutput::SetSingleWidthLine => self.set_line_size(LineSize::SingleWidth),
+            TerminalOutput::SetDoubleWidthLine => self.set_line_size(LineSize::DoubleWidth),
+            TerminalOutput::SetDoubleHeightLine { top_half: true } => self.set_line_size(LineSize::DoubleHeightTop),
+            TerminalOutput::SetDoubleHeightLine { top_half: false } => self.set_line_size(LineSize::DoubleHeightBottom),
+            TerminalOutput::ScreenAlignmentTest => self.alignment_test(),
+            TerminalOutput::FullReset => self.full_reset(),
+            TerminalOutput::SoftReset => self.soft_reset(),
+            TerminalOutput::SaveCursor => {
+                self.saved = SavedCursor { x: self.x, y: self.y, style: self.style, origin_mode: self.origin_mode };
+            }
+            TerminalOutput::RestoreCursor => {
+                let saved = self.saved;
+                self.style = saved.style;
+                self.origin_mode = saved.origin_mode;
+                self.move_to(saved.x.min(self.columns - 1), saved.y.min(self.rows - 1));
+            }
+            TerminalOutput::CopyRectangle { source, top, left } => self.copy_rectangle(source, *top, *left),
+            TerminalOutput::FillRectangle { ch, area } => {
+                let cell = Cell { c: *ch, style: self.style };
+                self.for_each_in(area, |current| *current = cell);
+            }
+            TerminalOutput::EraseRectangle(area) => self.for_each_in(area, |cell| *cell = Cell::default()),
+            TerminalOutput::SelectiveEraseRectangle(area) => self.for_each_in(area, |cell| {
+                if !cell.style.protected {
+                    cell.c = ' ';
+                }
+            }),
+            TerminalOutput::ChangeRectangleAttributes { area, attributes } => self.for_each_in(area, |cell| {
+                for &attribute in attributes {
+                    cell.style.change_attribute(attribute);
+                }
+            }),
+            TerminalOutput::ReverseRectangleAttributes { area, attributes } => self.for_each_in(area, |cell| {
+                for &attribute in attributes {
+                    cell.style.reverse_attribute(attribute);
+                }
+            }),
+            _ => {}
+        }
+    }
+
+    fn margin_left(&self) -> usize {
+        if self.left_right_margin_mode { self.left } else { 0 }
+    }
+
+    fn margin_right(&self) -> usize {
+        if self.left_right_margin_mode { self.right } else { self.columns - 1 }
+    }
+
+    /// Top, left, bottom and right limits for addressing: the margins in origin
+    /// mode, the whole screen otherwise
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        if self.origin_mode {
+            (self.top, self.margin_left(), self.bottom, self.margin_right())
+        } else {
+            (0, 0, self.rows - 1, self.columns - 1)
+        }
+    }
+
+    fn move_to(&mut self, x: usize, y: usize) {
+        self.x = x;
+        self.y = y;
+        self.pending_wrap = false;
+        self.clamp_to_line();
+    }
+
+    /// Keep the cursor inside the characters of a double-size line
+    fn clamp_to_line(&mut self) {
+        self.x = self.x.min(self.line_width(self.y) - 1);
+    }
+
+    /// Characters in the right half of a line are lost when it becomes double-size
+    fn set_line_size(&mut self, size: LineSize) {
+        self.line_sizes[self.y] = size;
+        let width = self.line_width(self.y);
+        self.lines[self.y][width..].fill(Cell::default());
+        self.pending_wrap = false;
+        self.clamp_to_line();
+    }
+
+    /// DECALN: fill the screen with `E`, reset the margins and line sizes, and
+    /// move the cursor home
+    fn alignment_test(&mut self) {
+        let cell = Cell { c: 'E', style: Style::default() };
+        self.lines.iter_mut().for_each(|line| line.fill(cell));
+        self.line_sizes.fill(LineSize::SingleWidth);
+        self.top = 0;
+        self.bottom = self.rows - 1;
+        self.left = 0;
+        self.right = self.columns - 1;
+        self.move_to(0, 0);
+    }
+
+    fn home(&mut self) {
+        let (top, left, _, _) = self.bounds();
+        self.move_to(left, top);
+    }
+
+    fn set_mode(&mut self, mode: &Mode, enable: bool) {
+        match mode {
+            Mode::Origin => {
+                self.origin_mode = enable;
+                self.home();
+            }
+            Mode::AutoWrap => {
+                self.auto_wrap = enable;
+                self.pending_wrap = false;
+            }
+            Mode::LeftRightMargin => {
+                self.left_right_margin_mode = enable;
+                if !enable {
+                    self.left = 0;
+                    self.right = self.columns - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        if self.pending_wrap {
+            self.carriage_return();
+            self.line_feed();
+        }
+        let right = if self.x <= self.margin_right() { self.margin_right() } else { self.columns - 1 };
+        let right = right.min(self.line_width(self.y) - 1);
+        self.lines[self.y][self.x] = Cell { c, style: self.style };
+        if self.x < right {
+            self.x += 1;
+        } else {
+            self.pending_wrap = self.auto_wrap;
+        }
+    }
+
+    fn carriage_return(&mut self) {
+        let left = if self.x >= self.margin_left() { self.margin_left() } else { 0 };
+        self.move_to(left, self.y);
+    }
+
+    fn line_feed(&mut self) {
+        self.pending_wrap = false;
+        if self.y == self.bottom {
+            self.scroll_up();
+        } else if self.y < self.rows - 1 {
+            self.y += 1;
+        }
+        self.clamp_to_line();
+    }
+
+    fn reverse_index(&mut self) {
+        self.pending_wrap = false;
+        if self.y == self.top {
+            self.scroll_down();
+        } else if self.y > 0 {
+            self.y -= 1;
+        }
+        self.clamp_to_line();
+    }
+
+    /// Move the lines of the scrolling region up by one, inside the left/right
+    /// margins. Line sizes only move with whole lines.
+    fn scroll_up(&mut self) {
+        let columns = self.margin_left()..=self.margin_right();
+        for row in self.top..self.bottom {
+            let below = self.lines[row + 1][columns.clone()].to_vec();
+            self.lines[row][columns.clone()].copy_from_slice(&below);
+        }
+        self.lines[self.bottom][columns].fill(Cell::default());
+        if !self.left_right_margin_mode {
+            self.line_sizes[self.top..=self.bottom].rotate_left(1);
+            self.line_sizes[self.bottom] = LineSize::SingleWidth;
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        let columns = self.margin_left()..=self.margin_right();
+        for row in (self.top + 1..=self.bottom).rev() {
+            let above = self.lines[row - 1][columns.clone()].to_vec();
+            self.lines[row][columns.clone()].copy_from_slice(&above);
+        }
+        self.lines[self.top][columns].fill(Cell::default());
+        if !self.left_right_margin_mode {
+            self.line_sizes[self.top..=self.bottom].rotate_right(1);
+            self.line_sizes[self.top] = LineSize::SingleWidth;
+        }
+    }
+
+    fn erase_display(&mut self, range: EraseRange, selective: bool) {
+        let rows = match range {
+            EraseRange::Forwards => self.y + 1..self.rows,
+            EraseRange::Backwards => 0..self.y,
+            EraseRange::All => 0..self.rows,
+        };
+        for row in rows {
+            self.erase(row, 0..=self.columns - 1, selective);
+        }
+        if range != EraseRange::All {
+            self.erase_in_line(range, selective);
+        }
+    }
+
+    fn erase_in_line(&mut self, range: EraseRange, selective: bool) {
+        let columns = match range {
+            EraseRange::Forwards => self.x..=self.columns - 1,
+            EraseRange::Backwards => 0..=self.x,
+            EraseRange::All => 0..=self.columns - 1,
+        };
+        self.erase(self.y, columns, selective);
+    }
+
+    /// Selective erases only blank the characters of unprotected cells; other
+    /// erases skip protected cells only inside an ISO guarded area. Erasing a
+    /// whole line makes it single-width.
+    fn erase(&mut self, row: usize, columns: RangeInclusive<usize>, selective: bool) {
+        if !selective && columns == (0..=self.columns - 1) {
+            self.line_sizes[row] = LineSize::SingleWidth;
+        }
+        let keep_protected = selective || self.iso_protection;
+        for cell in &mut self.lines[row][columns] {
+            if keep_protected && cell.style.protected {
+                continue;
+            }
+            if selective {
+                cell.c = ' ';
+            } else {
+                *cell = Cell::default();
+            }
+        }
+        self.pending_wrap = false;
+    }
+
+    /// DCH: shift the rest of the line (up to the right margin) left
+    fn delete_characters(&mut self, n: usize) {
+        let right = if self.x <= self.margin_right() { self.margin_right() } else { self.columns - 1 };
+        let line = &mut self.lines[self.y][self.x..=right];
+        let n = n.max(1).min(line.len());
+        line.rotate_left(n);
+        let len = line.len();
+        line[len - n..].fill(Cell::default());
+        self.pending_wrap = false;
+    }
+
+    /// ICH: shift the rest of the line (up to the right margin) right
+    fn insert_blanks(&mut self, n: usize) {
+        let right = if self.x <= self.margin_right() { self.margin_right() } else { self.columns - 1 };
+        let line = &mut self.lines[self.y][self.x..=right];
+        let n = n.max(1).min(line.len());
+        line.rotate_right(n);
+        line[..n].fill(Cell::default());
+        self.pending_wrap = false;
+    }
+
+    /// Rows and columns covered by `area`, clamped to the screen, or to the
+    /// margins in origin mode. `None` when the rectangle is empty.
+    fn resolve(&self, area: &Rect) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        let (top, left, bottom, right) = self.bounds();
+        let rows = top.saturating_add(area.top.saturating_sub(1))
+            ..=area.bottom.map_or(bottom, |b| top.saturating_add(b.saturating_sub(1)).min(bottom));
+        let columns = left.saturating_add(area.left.saturating_sub(1))
+            ..=area.right.map_or(right, |r| left.saturating_add(r.saturating_sub(1)).min(right));
+        (!rows.is_empty() && !columns.is_empty()).then_some((rows, columns))
+    }
+
+    fn for_each_in<F: FnMut(&mut Cell)>(&mut self, area: &Rect, mut f: F) {
+        let Some((rows, columns)) = self.resolve(area) else {
+            return;
+        };
+        for row in rows {
+            self.lines[row][columns.clone()].iter_mut().for_each(&mut f);
+        }
+    }
+
+    /// DECCRA: the copy is clipped like the source
+    fn copy_rectangle(&mut self, source: &Rect, top: usize, left: usize) {
+        let Some((rows, columns)) = self.resolve(source) else {
+            return;
+        };
+        let copied: Vec<Vec<Cell>> = rows.map(|row| self.lines[row][columns.clone()].to_vec()).collect();
+        let (bounds_top, bounds_left, bounds_bottom, bounds_right) = self.bounds();
+        let top = bounds_top.saturating_add(top.saturating_sub(1));
+        let left = bounds_left.saturating_add(left.saturating_sub(1));
+        for (row, cells) in (top..=bounds_bottom).zip(copied) {
+            for (column, cell) in (left..=bounds_right).zip(cells) {
+                self.lines[row][column] = cell;
+            }
+        }
+    }
+}
@@ -22,7 +22,7 @@ impl State {
     pub fn from_u8(value: u8) -> Option<Self> {
         if value <= Self::SosPmApcString as u8 {
             // SAFETY: The value is guaranteed to be within the valid range of the enum.
-            Some(unsafe { std::mem::transmute(value) })
+            Some(unsafe { std::mem::transmute::<u8, Self>(value) })
         } else {
             None
         }
@@ -54,7 +54,7 @@ impl Action {
     pub fn from_u8(value: u8) -> Option<Self> {
         if value <= Self::OscEnd as u8 {
             // SAFETY: The value is guaranteed to be within the valid range of the enum.
-            Some(unsafe { std::mem::transmute(value) })
+            Some(unsafe { std::mem::transmute::<u8, Self>(value) })
         } else {
             None
         }
@@ -67,10 +67,11 @@ pub const fn pack(state: State, action: Action) -> TableEntry {
     ((state as u8) << 4) | (action as u8)
 }
 
-// Classes: 0:Exe, 1:Print, 2:Param, 3:Inter, 4:CsiEntry, 5:Esc, 6:Disp, 7:Osc, 8:Sos, 9:DcsEntry
+// Classes: 0:Exe, 1:Print, 2:Param, 3:Inter, 4:CsiEntry, 5:Esc, 6:Disp, 7:Osc, 8:Sos, 9:DcsEntry,
+// 10:Bel, 11:Abort (CAN/SUB)
 pub const CLASS_TABLE: [u8; 256] = [
-    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 00-0F
-    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, // 10-1F
+    0, 0, 0, 0, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, // 00-0F (BEL = 10)
+    0, 0, 0, 0, 0, 0, 0, 0, 11, 0, 11, 5, 0, 0, 0, 0, // 10-1F (CAN, SUB = 11)
     3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, // 20-2F
     2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 30-3F
     6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, // 40-4F
@@ -104,7 +105,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::Ground, Action::Print),     // 7:Osc
         pack(State::Ground, Action::Print),     // 8:Sos
         pack(State::Ground, Action::Print),     // 9:DcsEntry (P treated as print)
-        0,0,0,0,0,0
+        pack(State::Ground, Action::Execute),   // 10:Bel
+        pack(State::Ground, Action::Execute),   // 11:Abort (CAN/SUB)
+        0,0,0,0
     ],
     // State 1: Escape
     [
@@ -116,9 +119,11 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::Escape, Action::Clear),               // 5
         pack(State::Ground, Action::EscDispatch),         // 6
         pack(State::OscString, Action::OscStart),         // 7
-        pack(State::SosPmApcString, Action::None),        // 8
+        pack(State::SosPmApcString, Action::OscStart),    // 8: APC (collected like OSC)
         pack(State::DcsEntry, Action::Clear),             // 9: DcsEntry (Trigger DCS)
-        0,0,0,0,0,0
+        pack(State::Escape, Action::Execute),       // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 2: EscapeIntermediate
     [
@@ -132,7 +137,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::Ground, Action::Ignore),              // 7
         pack(State::Ground, Action::Ignore),              // 8
         pack(State::Ground, Action::EscDispatch),         // 9: (Treat P as dispatch here)
-        0,0,0,0,0,0
+        pack(State::EscapeIntermediate, Action::Execute),// 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 3: CsiEntry
     [
@@ -146,7 +153,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::CsiIgnore, Action::None),             // 7
         pack(State::CsiIgnore, Action::None),             // 8
         pack(State::Ground, Action::CsiDispatch),         // 9: (Treat P as dispatch)
-        0,0,0,0,0,0
+        pack(State::CsiEntry, Action::Execute),     // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 4: CsiParam
     [
@@ -160,7 +169,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::CsiIgnore, Action::None),             // 7
         pack(State::CsiIgnore, Action::None),             // 8
         pack(State::Ground, Action::CsiDispatch),         // 9: (Treat P as dispatch)
-        0,0,0,0,0,0
+        pack(State::CsiParam, Action::Execute),     // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 5: CsiIntermediate
     [
@@ -174,7 +185,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::CsiIgnore, Action::None),             // 7
         pack(State::CsiIgnore, Action::None),             // 8
         pack(State::Ground, Action::CsiDispatch),         // 9: (Treat P as dispatch)
-        0,0,0,0,0,0
+        pack(State::CsiIntermediate, Action::Execute),// 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 6: CsiIgnore
     [
@@ -188,7 +201,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::CsiIgnore, Action::Ignore),           // 7
         pack(State::CsiIgnore, Action::Ignore),           // 8
         pack(State::Ground, Action::Ignore),              // 9
-        0,0,0,0,0,0
+        pack(State::CsiIgnore, Action::Execute),    // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 7: DcsEntry
     [
@@ -202,7 +217,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsPassthrough, Action::Hook),       // 9
-        0,0,0,0,0,0
+        pack(State::DcsEntry, Action::Execute),     // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 8: DcsParam
     [
@@ -216,7 +233,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsPassthrough, Action::Hook),       // 9
-        0,0,0,0,0,0
+        pack(State::DcsParam, Action::Execute),     // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 9: DcsIntermediate
     [
@@ -230,7 +249,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsPassthrough, Action::Hook),       // 9
-        0,0,0,0,0,0
+        pack(State::DcsIntermediate, Action::Execute),// 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 10: DcsPassthrough (Wait for ST)
     [
@@ -244,7 +265,9 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::DcsPassthrough, Action::Put),
         pack(State::DcsPassthrough, Action::Put),
         pack(State::DcsPassthrough, Action::Put),       // 9
-        0,0,0,0,0,0
+        pack(State::DcsPassthrough, Action::Put),   // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 11: DcsIgnore (Wait for ST)
     [
@@ -258,11 +281,13 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::DcsIgnore, Action::Ignore),
         pack(State::Ground, Action::Unhook),            // 9
-        0,0,0,0,0,0
+        pack(State::DcsIgnore, Action::Ignore),     // 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 12: OscString
     [
-        pack(State::Ground, Action::OscEnd),        // 0: Exe (BEL)
+        pack(State::OscString, Action::Ignore),     // 0: Exe (ignored, only BEL ends the string)
         pack(State::OscString, Action::OscPut),     // 1: Print
         pack(State::OscString, Action::OscPut),     // 2: Param
         pack(State::OscString, Action::OscPut),     // 3: Inter
@@ -272,20 +297,25 @@ pub const TRANSITION_TABLE: [[TableEntry; 16]; 14] = [
         pack(State::OscString, Action::OscPut),     // 7: Osc
         pack(State::OscString, Action::OscPut),     // 8: Sos
         pack(State::OscString, Action::OscPut),     // 9: DcsEntry (Put in OSC)
-        0,0,0,0,0,0
+        pack(State::Ground, Action::OscEnd),        // 10: BEL terminates
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
     // State 13: SosPmApcString
+    // Reuses the OSC string actions; the parser tells them apart by state
     [
         pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::Escape, Action::Clear),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),
-        pack(State::SosPmApcString, Action::Ignore),    // 9
-        0,0,0,0,0,0
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::Escape, Action::OscEnd),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),
+        pack(State::SosPmApcString, Action::OscPut),    // 9
+        pack(State::SosPmApcString, Action::Ignore),// 10: BEL
+        pack(State::Ground, Action::Execute),       // 11: CAN/SUB abort
+        0,0,0,0
     ],
 ];
\ No newline at end of file
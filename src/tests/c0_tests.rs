@@ -0,0 +1,66 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+fn data(text: &str) -> TerminalOutput {
+    TerminalOutput::Data(text.as_bytes().to_vec())
+}
+
+#[test]
+fn test_bell_tab_and_nul() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"a\x07b\tc\x00"), vec![
+        data("a"),
+        TerminalOutput::Bell,
+        data("b"),
+        TerminalOutput::HorizontalTab,
+        data("c"),
+    ]);
+}
+
+#[test]
+fn test_controls_inside_csi() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[1\x07;\t31m"), vec![
+        TerminalOutput::Bell,
+        TerminalOutput::HorizontalTab,
+        TerminalOutput::Sgr(SelectGraphicRendition::Bold),
+        TerminalOutput::Sgr(SelectGraphicRendition::ForegroundRed),
+    ]);
+}
+
+#[test]
+fn test_enquiry_answerback() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x05"), vec![TerminalOutput::Enquiry]);
+    assert!(parser.take_replies().is_empty());
+
+    parser.set_answerback(b"terminaux");
+    assert_eq!(parser.push(b"\x05"), vec![TerminalOutput::Enquiry]);
+    assert_eq!(parser.take_replies(), b"terminaux");
+}
+
+#[test]
+fn test_can_and_sub_abort_escape_and_csi() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[31\x18m"), vec![data("m")]);
+    assert_eq!(parser.push(b"\x1B[31\x1Am"), vec![data("\u{2426}m")]);
+    assert_eq!(parser.push(b"\x1B(\x18B"), vec![data("B")]);
+    assert_eq!(parser.push(b"\x1B\x18c"), vec![data("c")]);
+    // SUB in text is shown as the substitution glyph
+    assert_eq!(parser.push(b"a\x1Ab"), vec![data("a"), data("\u{2426}b")]);
+}
+
+#[test]
+fn test_can_and_sub_abort_strings() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B]0;title\x18x"), vec![data("x")]);
+    assert_eq!(parser.push(b"\x1BP$qm\x18x"), vec![data("x")]);
+    assert_eq!(parser.push(b"\x1BP\x80\x18x"), vec![data("x")]);
+    assert_eq!(parser.push(b"\x1B_apc\x1Ax"), vec![data("\u{2426}x")]);
+}
+
+#[test]
+fn test_other_controls_are_ignored_in_osc() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B]2;ti\ntl\x7Fe\x07"), vec![TerminalOutput::SetTitle("title".to_string())]);
+}
@@ -0,0 +1,76 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::handlers::HandlerResponse;
+
+#[test]
+fn test_c1_controls_off_by_default() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x9B1m");
+    assert_eq!(output, vec![TerminalOutput::Data(b"\x9B1m".to_vec())]);
+}
+
+#[test]
+fn test_c1_controls() {
+    let mut parser = AnsiParser::new();
+    parser.set_c1_controls(true);
+
+    let output = parser.push(b"a\x9B1;31mb");
+    assert_eq!(output, vec![
+        TerminalOutput::Data(b"a".to_vec()),
+        TerminalOutput::Sgr(SelectGraphicRendition::Bold),
+        TerminalOutput::Sgr(SelectGraphicRendition::ForegroundRed),
+        TerminalOutput::Data(b"b".to_vec()),
+    ]);
+
+    // OSC and DCS strings terminated by 8-bit ST
+    let output = parser.push(b"\x9D2;t\x9C\x90$qm\x9C");
+    assert_eq!(output, vec![
+        TerminalOutput::SetTitle("t".to_string()),
+        TerminalOutput::DeviceControlString { params: vec![], intermediates: b"$".to_vec(), data: b"qm".to_vec() },
+    ]);
+
+    // A stray byte that can't continue a UTF-8 sequence is a control (NEL)
+    let output = parser.push(b"x\x85");
+    assert_eq!(output, vec![TerminalOutput::Data(b"x".to_vec()), TerminalOutput::NextLine]);
+}
+
+#[test]
+fn test_c1_controls_leave_utf8_alone() {
+    let mut parser = AnsiParser::new();
+    parser.set_c1_controls(true);
+
+    // U+2713 is E2 9C 93; 0x9C must not end the title
+    let output = parser.push("\x1B]2;✓ done\x07é✓…".as_bytes());
+    assert_eq!(output, vec![
+        TerminalOutput::SetTitle("✓ done".to_string()),
+        TerminalOutput::Data("é✓…".as_bytes().to_vec()),
+    ]);
+
+    // Split across pushes, the character is held back until it is complete
+    let bytes = "✓".as_bytes();
+    let mut output = parser.push(&bytes[..1]);
+    output.extend(parser.push(&bytes[1..]));
+    assert_eq!(output, vec![TerminalOutput::Data("✓".as_bytes().to_vec())]);
+}
+
+#[test]
+fn test_control_encoding() {
+    let mut parser = AnsiParser::new();
+    parser.register_osc_handler(5000, |_, _| Some(HandlerResponse::default().with_reply(&b"\x1B[?1;2c\x1B]5000;x\x1B\\"[..])));
+    assert_eq!(parser.control_encoding(), ControlEncoding::SevenBit);
+
+    let output = parser.push(b"\x1B G\x1B]5000\x07");
+    assert_eq!(output, vec![TerminalOutput::SetControlEncoding(ControlEncoding::EightBit)]);
+    assert_eq!(parser.control_encoding(), ControlEncoding::EightBit);
+    assert_eq!(parser.take_replies(), b"\x9B?1;2c\x9D5000;x\x9C".to_vec());
+
+    let output = parser.push(b"\x1B F\x1B]5000\x07");
+    assert_eq!(output, vec![TerminalOutput::SetControlEncoding(ControlEncoding::SevenBit)]);
+    assert_eq!(parser.take_replies(), b"\x1B[?1;2c\x1B]5000;x\x1B\\".to_vec());
+}
+
+#[test]
+fn test_convert_reply() {
+    assert_eq!(ControlEncoding::EightBit.convert(b"\x1B[3;1R\x1BPx\x1B\\\x1Bc\x1B"), b"\x9B3;1R\x90x\x9C\x1Bc\x1B");
+    assert_eq!(ControlEncoding::SevenBit.convert(b"\x1B[3;1R"), b"\x1B[3;1R");
+}
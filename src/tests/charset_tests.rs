@@ -0,0 +1,134 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+fn data(text: &str) -> TerminalOutput {
+    TerminalOutput::Data(text.as_bytes().to_vec())
+}
+
+#[test]
+fn test_uk_and_technical_charsets() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B(A#\x1B)>\x0E\x3C\x2B\x0F#");
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G0, charset: StandardCharset::Uk },
+        data("£"),
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G1, charset: StandardCharset::DecTechnical },
+        TerminalOutput::SetActiveCharset(CharsetIndex::G1),
+        data("≤⎛"),
+        TerminalOutput::SetActiveCharset(CharsetIndex::G0),
+        data("£"),
+    ]);
+}
+
+#[test]
+fn test_locking_shifts_into_gl() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B*0\x1B+%5\x1Bnq\x1Bo(\x0Fq");
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G2, charset: StandardCharset::SpecialCharacterAndLineDrawing },
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G3, charset: StandardCharset::DecSupplemental },
+        TerminalOutput::SetActiveCharset(CharsetIndex::G2),
+        data("─"),
+        TerminalOutput::SetActiveCharset(CharsetIndex::G3),
+        data("¤"),
+        TerminalOutput::SetActiveCharset(CharsetIndex::G0),
+        data("q"),
+    ]);
+}
+
+#[test]
+fn test_single_shift_applies_to_one_character() {
+    let mut parser = AnsiParser::new();
+    parser.push(b"\x1B*0");
+    let output = parser.push(b"\x1BNqq");
+    assert_eq!(output, vec![TerminalOutput::SingleShift(CharsetIndex::G2), data("─q")]);
+
+    // A non-ASCII character uses up the shift too
+    let output = parser.push("\x1BN✓q".as_bytes());
+    assert_eq!(output, vec![TerminalOutput::SingleShift(CharsetIndex::G2), data("✓q")]);
+
+    // 8-bit SS3
+    parser.set_c1_controls(true);
+    parser.push(b"\x1B+>");
+    let output = parser.push(b"\x8Fzz");
+    assert_eq!(output, vec![TerminalOutput::SingleShift(CharsetIndex::G3), data("ζz")]);
+}
+
+#[test]
+fn test_gr_invocation() {
+    let mut parser = AnsiParser::new();
+    // Latin-1 characters are left alone while GR holds ASCII
+    assert_eq!(parser.push("¨".as_bytes()), vec![data("¨")]);
+
+    // GR only applies to raw single-byte input
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    let output = parser.push(b"\x1B)%5\x1B~\xA8\xD7\x1B}\xA8");
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G1, charset: StandardCharset::DecSupplemental },
+        TerminalOutput::SetActiveGrCharset(CharsetIndex::G1),
+        data("¤Œ"),
+        TerminalOutput::SetActiveGrCharset(CharsetIndex::G2),
+        data("¨"),
+    ]);
+}
+
+#[test]
+fn test_gr_leaves_utf8_alone() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push("\x1B*%5\x1B}é¨".as_bytes());
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G2, charset: StandardCharset::DecSupplemental },
+        TerminalOutput::SetActiveGrCharset(CharsetIndex::G2),
+        data("é¨"),
+    ]);
+}
+
+#[test]
+fn test_96_character_designation() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B-A\x1B.A\x1B/A\x1B)A");
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G1, charset: StandardCharset::Latin1Supplemental },
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G2, charset: StandardCharset::Latin1Supplemental },
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G3, charset: StandardCharset::Latin1Supplemental },
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G1, charset: StandardCharset::Uk },
+    ]);
+
+    let output = parser.push(b"\x1B-A\x0EI\x0F");
+    assert_eq!(output[2], data("É"));
+}
+
+#[test]
+fn test_national_charsets_need_decnrcm() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B(K{"), vec![data("{")]);
+
+    let output = parser.push(b"\x1B[?42h\x1B(K{|}~\x1B(%6[\x1B(f@");
+    assert_eq!(output, vec![
+        TerminalOutput::SetMode(Mode::NationalReplacement),
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G0, charset: StandardCharset::German },
+        data("äöüß"),
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G0, charset: StandardCharset::Portuguese },
+        data("Ã"),
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G0, charset: StandardCharset::French },
+        data("à"),
+    ]);
+
+    // Leaving DECNRCM keeps the designated set
+    let output = parser.push(b"\x1B[?42l@\x1B(Z");
+    assert_eq!(output, vec![TerminalOutput::ResetMode(Mode::NationalReplacement), data("à")]);
+}
+
+#[test]
+fn test_charset_encoding() {
+    let items = [
+        (TerminalOutput::ConfigureCharset { index: CharsetIndex::G3, charset: StandardCharset::Portuguese }, &b"\x1B+%6"[..]),
+        (TerminalOutput::ConfigureCharset { index: CharsetIndex::G2, charset: StandardCharset::Latin1Supplemental }, b"\x1B.A"),
+        (TerminalOutput::ConfigureCharset { index: CharsetIndex::G0, charset: StandardCharset::Latin1Supplemental }, b""),
+        (TerminalOutput::SetActiveGrCharset(CharsetIndex::G3), b"\x1B|"),
+        (TerminalOutput::SingleShift(CharsetIndex::G3), b"\x1BO"),
+    ];
+    for (item, encoded) in items {
+        assert_eq!(item.encode(), encoded, "{:?}", item);
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+
+use crate::AnsiParser;
+use crate::clipboard::*;
+use crate::definitions::*;
+
+#[test]
+fn test_base64_round_trip() {
+    for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar", &[0, 255, 128, 7]] {
+        assert_eq!(base64_decode(&base64_encode(data)).as_deref(), Some(data));
+    }
+    assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    assert_eq!(base64_encode(b"fo"), "Zm8=");
+    assert_eq!(base64_decode("Zm8"), Some(b"fo".to_vec()));
+    assert_eq!(base64_decode("Z"), None);
+    assert_eq!(base64_decode("Zm9v!"), None);
+}
+
+#[test]
+fn test_osc52_set() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]52;cp;aGVsbG8=\x07");
+    assert_eq!(output, vec![TerminalOutput::ClipboardSet {
+        selection: vec![Selection::Clipboard, Selection::Primary],
+        data: b"hello".to_vec(),
+    }]);
+}
+
+#[test]
+fn test_osc52_default_selection_and_cut_buffers() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]52;;eA==\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::ClipboardSet {
+        selection: vec![Selection::Select, Selection::CutBuffer(0)],
+        data: b"x".to_vec(),
+    }]);
+
+    let output = parser.push(b"\x1B]52;s7;eA==\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::ClipboardSet {
+        selection: vec![Selection::Select, Selection::CutBuffer(7)],
+        data: b"x".to_vec(),
+    }]);
+}
+
+#[test]
+fn test_osc52_query_and_clear() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]52;c;?\x07");
+    assert_eq!(output, vec![TerminalOutput::ClipboardQuery {
+        selection: vec![Selection::Clipboard],
+        terminator: StringTerminator::Bel,
+    }]);
+
+    let output = parser.push(b"\x1B]52;c;\x07");
+    assert_eq!(output, vec![TerminalOutput::ClipboardClear { selection: vec![Selection::Clipboard] }]);
+
+    // Not base64: xterm clears the selection
+    let output = parser.push(b"\x1B]52;c;!!!\x07");
+    assert_eq!(output, vec![TerminalOutput::ClipboardClear { selection: vec![Selection::Clipboard] }]);
+}
+
+#[test]
+fn test_osc52_max_size() {
+    let mut parser = AnsiParser::new();
+    parser.set_clipboard_max_size(4);
+
+    let output = parser.push(b"\x1B]52;c;YWJjZA==\x07");
+    assert_eq!(output.len(), 1);
+
+    let output = parser.push(b"\x1B]52;c;YWJjZGU=\x07");
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_osc52_unlimited_max_size() {
+    let mut parser = AnsiParser::new();
+    parser.set_clipboard_max_size(usize::MAX);
+
+    let output = parser.push(b"\x1B]52;c;YWJjZA==\x07");
+    assert_eq!(output, vec![TerminalOutput::ClipboardSet {
+        selection: vec![Selection::Clipboard],
+        data: b"abcd".to_vec(),
+    }]);
+}
+
+#[test]
+fn test_osc52_policy() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+
+    let mut parser = AnsiParser::new();
+    parser.set_clipboard_policy(move |access, selection| {
+        log.lock().unwrap().push((access, selection.to_vec()));
+        access == ClipboardAccess::Write
+    });
+
+    assert!(parser.push(b"\x1B]52;c;?\x07").is_empty());
+    assert_eq!(parser.push(b"\x1B]52;c;eA==\x07").len(), 1);
+    assert_eq!(*seen.lock().unwrap(), vec![
+        (ClipboardAccess::Read, vec![Selection::Clipboard]),
+        (ClipboardAccess::Write, vec![Selection::Clipboard]),
+    ]);
+}
+
+#[test]
+fn test_osc52_reply() {
+    let reply = clipboard_reply(&[Selection::Clipboard], b"hello", StringTerminator::St);
+    assert_eq!(reply, b"\x1B]52;c;aGVsbG8=\x1B\\".to_vec());
+
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(&reply), vec![TerminalOutput::ClipboardSet {
+        selection: vec![Selection::Clipboard],
+        data: b"hello".to_vec(),
+    }]);
+}
@@ -0,0 +1,92 @@
+use crate::AnsiParser;
+use crate::color::*;
+use crate::definitions::*;
+
+#[test]
+fn test_parse_color_spec_forms() {
+    assert_eq!(parse_color_spec("rgb:ff/80/00"), Some(Rgb::new(255, 128, 0)));
+    assert_eq!(parse_color_spec("rgb:f/8/0"), Some(Rgb::new(255, 136, 0)));
+    assert_eq!(parse_color_spec("rgb:ffff/0000/8000"), Some(Rgb::new(255, 0, 128)));
+    assert_eq!(parse_color_spec("#1e90ff"), Some(Rgb::new(0x1e, 0x90, 0xff)));
+    assert_eq!(parse_color_spec("#f80"), Some(Rgb::new(0xf0, 0x80, 0x00)));
+    assert_eq!(parse_color_spec("#123456789abc"), Some(Rgb::new(0x12, 0x56, 0x9a)));
+    assert_eq!(parse_color_spec("rgbi:1/0.5/0"), Some(Rgb::new(255, 128, 0)));
+    assert_eq!(parse_color_spec("Dodger Blue"), Some(Rgb::new(30, 144, 255)));
+    assert_eq!(parse_color_spec("gray50"), Some(Rgb::new(127, 127, 127)));
+    assert_eq!(parse_color_spec("grey100"), Some(Rgb::new(255, 255, 255)));
+
+    assert_eq!(parse_color_spec("rgb:ff/80"), None);
+    assert_eq!(parse_color_spec("rgb:fffff/0/0"), None);
+    assert_eq!(parse_color_spec("#12345"), None);
+    assert_eq!(parse_color_spec("rgbi:2/0/0"), None);
+    assert_eq!(parse_color_spec("gray101"), None);
+    assert_eq!(parse_color_spec("notacolor"), None);
+}
+
+#[test]
+fn test_osc_palette_set_and_query() {
+    let mut parser = AnsiParser::new();
+
+    let output = parser.push(b"\x1B]4;1;rgb:ff/00/00;2;?\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::SetPaletteColor { index: 1, color: Rgb::new(255, 0, 0) },
+        TerminalOutput::QueryPaletteColor { index: 2, terminator: StringTerminator::Bel },
+    ]);
+
+    let output = parser.push(b"\x1B]104\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::ResetPaletteColor(None)]);
+
+    let output = parser.push(b"\x1B]104;3;4\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::ResetPaletteColor(Some(3)),
+        TerminalOutput::ResetPaletteColor(Some(4)),
+    ]);
+}
+
+#[test]
+fn test_osc_dynamic_colors() {
+    let mut parser = AnsiParser::new();
+
+    let output = parser.push(b"\x1B]11;?\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::QueryDynamicColor {
+        kind: DynamicColor::Background,
+        terminator: StringTerminator::St,
+    }]);
+
+    // Extra specs spill over to the following colours
+    let output = parser.push(b"\x1B]10;#ffffff;black\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::SetDynamicColor { kind: DynamicColor::Foreground, color: Rgb::new(255, 255, 255) },
+        TerminalOutput::SetDynamicColor { kind: DynamicColor::Background, color: Rgb::new(0, 0, 0) },
+    ]);
+
+    let output = parser.push(b"\x1B]112\x07");
+    assert_eq!(output, vec![TerminalOutput::ResetDynamicColor(DynamicColor::Cursor)]);
+}
+
+#[test]
+fn test_osc_color_invalid_spec_stays_raw() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]4;1;bogus\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 4, payload: b"1;bogus".to_vec() }]);
+}
+
+#[test]
+fn test_color_replies() {
+    assert_eq!(
+        palette_color_reply(1, Rgb::new(0xcd, 0, 0), StringTerminator::St),
+        b"\x1B]4;1;rgb:cdcd/0000/0000\x1B\\".to_vec()
+    );
+    assert_eq!(
+        dynamic_color_reply(DynamicColor::Background, Rgb::new(0x12, 0x34, 0x56), StringTerminator::Bel),
+        b"\x1B]11;rgb:1212/3434/5656\x07".to_vec()
+    );
+
+    // Replies parse back to the reported colour
+    let mut parser = AnsiParser::new();
+    let reply = dynamic_color_reply(DynamicColor::Foreground, Rgb::new(1, 2, 3), StringTerminator::St);
+    assert_eq!(parser.push(&reply), vec![TerminalOutput::SetDynamicColor {
+        kind: DynamicColor::Foreground,
+        color: Rgb::new(1, 2, 3),
+    }]);
+}
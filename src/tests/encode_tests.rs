@@ -0,0 +1,381 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+/// Small deterministic xorshift generator so the property test is reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn byte_in(&mut self, lo: u8, hi: u8) -> u8 {
+        lo + self.below((hi - lo) as u64 + 1) as u8
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    fn bytes(&mut self, min_len: u64, lo: u8, hi: u8) -> Vec<u8> {
+        let len = min_len + self.below(12);
+        (0..len).map(|_| self.byte_in(lo, hi)).collect()
+    }
+}
+
+fn arbitrary_sgr(rng: &mut Rng) -> SelectGraphicRendition {
+    use SelectGraphicRendition::*;
+    match rng.below(6) {
+        0 => Foreground8Bit(rng.next() as u8),
+        1 => Background8Bit(rng.next() as u8),
+        2 => ForegroundTrueColor(rng.next() as u8, rng.next() as u8, rng.next() as u8),
+        3 => BackgroundTrueColor(rng.next() as u8, rng.next() as u8, rng.next() as u8),
+        // Any simple code maps back through `from_usize`, including the unknown ones
+        _ => SelectGraphicRendition::from_usize(rng.below(120) as usize),
+    }
+}
+
+/// Unicode text including control characters
+fn text(rng: &mut Rng) -> String {
+    let bytes = rng.bytes(0, 0x00, 0x7F);
+    String::from_utf8(bytes).unwrap() + if rng.bool() { "é✓" } else { "" }
+}
+
+/// Unicode text without control characters
+fn printable(rng: &mut Rng) -> String {
+    String::from_utf8(rng.bytes(0, 0x20, 0x7E)).unwrap() + if rng.bool() { "é✓" } else { "" }
+}
+
+fn charset_index(rng: &mut Rng) -> CharsetIndex {
+    [CharsetIndex::G0, CharsetIndex::G1, CharsetIndex::G2, CharsetIndex::G3][rng.below(4) as usize]
+}
+
+fn title_target(rng: &mut Rng) -> TitleTarget {
+    [TitleTarget::Both, TitleTarget::IconName, TitleTarget::Title][rng.below(3) as usize]
+}
+
+fn window_op(rng: &mut Rng) -> WindowOp {
+    let size = |rng: &mut Rng| rng.bool().then(|| rng.below(5000) as usize + 1);
+    match rng.below(21) {
+        0 => WindowOp::Deiconify,
+        1 => WindowOp::Iconify,
+        2 => WindowOp::Move { x: rng.below(5000) as usize, y: rng.below(5000) as usize },
+        3 => WindowOp::ResizePixels { width: size(rng), height: size(rng) },
+        4 => WindowOp::Raise,
+        5 => WindowOp::Lower,
+        6 => WindowOp::Refresh,
+        7 => WindowOp::ResizeChars { columns: size(rng), rows: size(rng) },
+        8 => WindowOp::Maximize(
+            [MaximizeMode::Restore, MaximizeMode::Both, MaximizeMode::Vertically, MaximizeMode::Horizontally]
+                [rng.below(4) as usize],
+        ),
+        9 => WindowOp::Fullscreen(
+            [FullscreenMode::Exit, FullscreenMode::Enter, FullscreenMode::Toggle][rng.below(3) as usize],
+        ),
+        10 => WindowOp::ReportState,
+        11 => WindowOp::ReportPosition { text_area: rng.bool() },
+        12 => WindowOp::ReportSizePixels { text_area: rng.bool() },
+        13 => WindowOp::ReportScreenSizePixels,
+        14 => WindowOp::ReportCellSizePixels,
+        15 => WindowOp::ReportSizeChars,
+        16 => WindowOp::ReportScreenSizeChars,
+        17 => WindowOp::ReportIconName,
+        18 => WindowOp::ReportTitle,
+        _ => WindowOp::ResizeLines(24 + rng.below(200) as usize),
+    }
+}
+
+fn arbitrary_rect(rng: &mut Rng) -> Rect {
+    Rect {
+        top: 1 + rng.below(30) as usize,
+        left: 1 + rng.below(100) as usize,
+        bottom: rng.bool().then(|| 1 + rng.below(30) as usize),
+        right: rng.bool().then(|| 1 + rng.below(100) as usize),
+    }
+}
+
+fn arbitrary_rectangle_op(rng: &mut Rng) -> TerminalOutput {
+    use SelectGraphicRendition::*;
+    let reversible = [Reset, Bold, Underline, BlinkSlow, Reverse];
+    let changeable = [Reset, Bold, Underline, BlinkSlow, Reverse, NormalIntensity, NotUnderline, Unknown(25), Unknown(27)];
+    match rng.below(6) {
+        0 => TerminalOutput::CopyRectangle {
+            source: arbitrary_rect(rng),
+            top: 1 + rng.below(30) as usize,
+            left: 1 + rng.below(100) as usize,
+        },
+        1 => TerminalOutput::FillRectangle {
+            ch: if rng.bool() { rng.byte_in(b' ', b'~') as char } else { rng.byte_in(0xA0, 0xFF) as char },
+            area: arbitrary_rect(rng),
+        },
+        2 => TerminalOutput::EraseRectangle(arbitrary_rect(rng)),
+        3 => TerminalOutput::SelectiveEraseRectangle(arbitrary_rect(rng)),
+        4 => TerminalOutput::ChangeRectangleAttributes {
+            area: arbitrary_rect(rng),
+            attributes: (0..1 + rng.below(3)).map(|_| changeable[rng.below(9) as usize]).collect(),
+        },
+        _ => TerminalOutput::ReverseRectangleAttributes {
+            area: arbitrary_rect(rng),
+            attributes: (0..1 + rng.below(3)).map(|_| reversible[rng.below(5) as usize]).collect(),
+        },
+    }
+}
+
+/// Every variant the parser can currently produce. Not generated: `Invalid`,
+/// `SetLeftRightMargins`, `ResetMode(Mode::Ansi)` and national replacement sets.
+fn arbitrary_output(rng: &mut Rng) -> TerminalOutput {
+    let count = |rng: &mut Rng| rng.below(500) as usize;
+    let position = |rng: &mut Rng| 1 + rng.below(500) as usize;
+    let color = |rng: &mut Rng| Rgb::new(rng.next() as u8, rng.next() as u8, rng.next() as u8);
+    let dynamic = |rng: &mut Rng| [DynamicColor::Foreground, DynamicColor::Background, DynamicColor::Cursor][rng.below(3) as usize];
+    let terminator = |rng: &mut Rng| if rng.bool() { StringTerminator::Bel } else { StringTerminator::St };
+    let selection = |rng: &mut Rng| {
+        (0..1 + rng.below(3))
+            .map(|_| Selection::from_byte(b"cpqs01234567"[rng.below(12) as usize]).unwrap())
+            .collect::<Vec<_>>()
+    };
+    match rng.below(66) {
+        0 => TerminalOutput::Data(rng.bytes(1, 0x20, 0x7E)),
+        1 => TerminalOutput::Osc {
+            // Stay clear of the numbers that have typed variants
+            command: 5000 + rng.below(1000) as usize,
+            // DEL is dropped inside strings, like C0 controls other than BEL, CAN and SUB
+            payload: rng.bytes(0, 0x20, 0xFF).into_iter().filter(|&b| b != 0x7F).collect(),
+        },
+        2 => {
+            // '[', ']' and '_' aren't accepted as DCS finals by the transition table
+            let final_byte = match rng.byte_in(0x40, 0x7E) {
+                b'[' | b']' | b'_' => b'q',
+                b => b,
+            };
+            let mut data = vec![final_byte];
+            data.extend(rng.bytes(0, 0x20, 0x7E));
+            TerminalOutput::DeviceControlString {
+                params: (0..rng.below(4)).map(|_| count(rng)).collect(),
+                intermediates: (0..rng.below(2)).map(|_| rng.byte_in(0x20, 0x2F)).collect(),
+                data,
+            }
+        }
+        3 => TerminalOutput::SetCursorPos { x: Some(position(rng)), y: Some(position(rng)) },
+        4 => TerminalOutput::SetCursorPos { x: Some(position(rng)), y: None },
+        5 => TerminalOutput::SetCursorPos { x: None, y: Some(position(rng)) },
+        6 => TerminalOutput::CursorUp(count(rng)),
+        7 => TerminalOutput::CursorDown(count(rng)),
+        8 => TerminalOutput::CursorForward(count(rng)),
+        9 => TerminalOutput::CursorBackward(count(rng)),
+        10 => TerminalOutput::ClearForwards,
+        11 => TerminalOutput::ClearAll,
+        12 => TerminalOutput::ClearLineForwards,
+        13 => TerminalOutput::Delete(count(rng)),
+        14 => TerminalOutput::InsertSpaces(count(rng)),
+        15 => TerminalOutput::Backspace,
+        16 => TerminalOutput::Newline,
+        17 => TerminalOutput::CarriageReturn,
+        18 => TerminalOutput::Sgr(arbitrary_sgr(rng)),
+        19 => TerminalOutput::SetCursorVisibility(rng.bool()),
+        20 => {
+            let modes = [
+                Mode::Decckm,
+                Mode::BracketedPaste,
+                Mode::ModifyOtherKeys,
+                Mode::Insert,
+                Mode::LineFeedNewLine,
+                Mode::KeypadApplication,
+                Mode::NationalReplacement,
+                Mode::Columns132,
+                Mode::ReverseVideo,
+                Mode::Origin,
+                Mode::AutoWrap,
+                Mode::ReverseWraparound,
+                Mode::ExtendedReverseWraparound,
+                Mode::AltScreen,
+                Mode::AltScreenClear,
+                Mode::SaveCursor,
+                Mode::BackarrowKey,
+                Mode::LeftRightMargin,
+                Mode::FocusReporting,
+                Mode::Unknown(b"?9".to_vec()),
+            ];
+            let mode = modes[rng.below(modes.len() as u64) as usize].clone();
+            if rng.bool() { TerminalOutput::SetMode(mode) } else { TerminalOutput::ResetMode(mode) }
+        }
+        21 => if rng.bool() { TerminalOutput::EnterAltScreen } else { TerminalOutput::ExitAltScreen },
+        22 => TerminalOutput::DeviceControl { code: rng.byte_in(0x11, 0x14) },
+        23 => TerminalOutput::SetScrollingRegion {
+            top: rng.bool().then(|| position(rng)),
+            bottom: rng.bool().then(|| position(rng)),
+        },
+        24 => if rng.bool() { TerminalOutput::BeginSynchronizedUpdate } else { TerminalOutput::EndSynchronizedUpdate },
+        25 => {
+            let shapes = [CursorShape::Block, CursorShape::Underline, CursorShape::Beam];
+            TerminalOutput::SetCursorStyle {
+                shape: shapes[rng.below(3) as usize],
+                blinking: rng.bool(),
+            }
+        }
+        26 => TerminalOutput::SetActiveCharset(charset_index(rng)),
+        27 => {
+            let charsets = [
+                StandardCharset::Ascii,
+                StandardCharset::SpecialCharacterAndLineDrawing,
+                StandardCharset::Uk,
+                StandardCharset::DecSupplemental,
+                StandardCharset::DecTechnical,
+                StandardCharset::Latin1Supplemental,
+            ];
+            let charset = charsets[rng.below(charsets.len() as u64) as usize];
+            let index = charset_index(rng);
+            // 96-character sets can't be designated into G0
+            let index = if charset.is_96_character() && index == CharsetIndex::G0 { CharsetIndex::G1 } else { index };
+            TerminalOutput::ConfigureCharset { index, charset }
+        }
+        28 => TerminalOutput::SetDoubleHeightLine { top_half: rng.bool() },
+        29 => TerminalOutput::SetPaletteColor { index: rng.next() as u8, color: color(rng) },
+        30 => TerminalOutput::QueryPaletteColor { index: rng.next() as u8, terminator: terminator(rng) },
+        31 => TerminalOutput::ResetPaletteColor(rng.bool().then(|| rng.next() as u8)),
+        32 => TerminalOutput::SetDynamicColor { kind: dynamic(rng), color: color(rng) },
+        33 => TerminalOutput::QueryDynamicColor { kind: dynamic(rng), terminator: terminator(rng) },
+        34 => TerminalOutput::ResetDynamicColor(dynamic(rng)),
+        35 => TerminalOutput::ClipboardSet { selection: selection(rng), data: rng.bytes(1, 0x00, 0xFF) },
+        36 => TerminalOutput::ClipboardQuery { selection: selection(rng), terminator: terminator(rng) },
+        37 => TerminalOutput::ClipboardClear { selection: selection(rng) },
+        38 => TerminalOutput::SetHyperlink {
+            id: rng.bool().then(|| String::from_utf8(rng.bytes(1, b'a', b'z')).unwrap()),
+            uri: format!("https://example.com/{}", String::from_utf8(rng.bytes(0, b'a', b'z')).unwrap()),
+        },
+        39 => TerminalOutput::ClearHyperlink,
+        40 => TerminalOutput::NamedOsc {
+            command: rng.bytes(1, b'A', b'Z'),
+            payload: rng.bytes(0, 0x20, 0x7E),
+        },
+        41 => TerminalOutput::PromptStart,
+        42 => TerminalOutput::CommandStart,
+        43 => TerminalOutput::CommandExecuted,
+        44 => TerminalOutput::CommandFinished {
+            exit_code: rng.bool().then(|| rng.next() as i32),
+        },
+        45 => TerminalOutput::WorkingDirectory {
+            host: rng.bool().then(|| String::from_utf8(rng.bytes(1, 0x20, 0x7E)).unwrap()),
+            path: [&b"/"[..], &rng.bytes(0, 0x00, 0xFF)].concat(),
+        },
+        46 => TerminalOutput::Notification {
+            title: rng.bool().then(|| text(rng)),
+            body: text(rng),
+            id: rng.bool().then(|| String::from_utf8(rng.bytes(1, b'a', b'z')).unwrap()),
+            urgency: [Urgency::Low, Urgency::Normal, Urgency::Critical][rng.below(3) as usize],
+        },
+        47 => TerminalOutput::Progress {
+            state: [
+                ProgressState::Hidden,
+                ProgressState::Normal,
+                ProgressState::Error,
+                ProgressState::Indeterminate,
+                ProgressState::Paused,
+            ][rng.below(5) as usize],
+            percent: rng.bool().then(|| rng.below(101) as u8),
+        },
+        48 => TerminalOutput::SetTitle(printable(rng)),
+        49 => TerminalOutput::SetIconName(printable(rng)),
+        50 => TerminalOutput::PushTitle(title_target(rng)),
+        51 => TerminalOutput::PopTitle(title_target(rng)),
+        52 => TerminalOutput::WindowOp(window_op(rng)),
+        53 => TerminalOutput::SetControlEncoding(
+            if rng.bool() { ControlEncoding::SevenBit } else { ControlEncoding::EightBit },
+        ),
+        54 => TerminalOutput::SetActiveGrCharset([CharsetIndex::G1, CharsetIndex::G2, CharsetIndex::G3][rng.below(3) as usize]),
+        55 => TerminalOutput::SingleShift(if rng.bool() { CharsetIndex::G2 } else { CharsetIndex::G3 }),
+        56 => TerminalOutput::SetInputEncoding(if rng.bool() { InputEncoding::Utf8 } else { InputEncoding::SingleByte }),
+        57 => [
+            TerminalOutput::VerticalTab,
+            TerminalOutput::FormFeed,
+            TerminalOutput::Index,
+            TerminalOutput::ReverseIndex,
+            TerminalOutput::NextLine,
+        ]
+        .into_iter()
+        .nth(rng.below(5) as usize)
+        .unwrap(),
+        58 => if rng.bool() { TerminalOutput::SaveCursor } else { TerminalOutput::RestoreCursor },
+        59 => arbitrary_rectangle_op(rng),
+        61 => {
+            let range = [EraseRange::Forwards, EraseRange::Backwards, EraseRange::All][rng.below(3) as usize];
+            match rng.below(5) {
+                0 => TerminalOutput::SetCharacterProtection(rng.bool()),
+                1 => TerminalOutput::StartGuardedArea,
+                2 => TerminalOutput::EndGuardedArea,
+                3 => TerminalOutput::SelectiveEraseDisplay(range),
+                _ => TerminalOutput::SelectiveEraseLine(range),
+            }
+        }
+        62 => if rng.bool() { TerminalOutput::FullReset } else { TerminalOutput::SoftReset },
+        63 => TerminalOutput::ScreenAlignmentTest,
+        64 => [TerminalOutput::HorizontalTab, TerminalOutput::Bell, TerminalOutput::Enquiry]
+            .into_iter()
+            .nth(rng.below(3) as usize)
+            .unwrap(),
+        60 => TerminalOutput::RequestChecksum {
+            id: rng.below(100) as usize,
+            page: 1 + rng.below(4) as usize,
+            area: arbitrary_rect(rng),
+        },
+        _ => if rng.bool() { TerminalOutput::SetDoubleWidthLine } else { TerminalOutput::SetSingleWidthLine },
+    }
+}
+
+#[test]
+fn test_encode_round_trip_property() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..5000 {
+        let item = arbitrary_output(&mut rng);
+        let encoded = item.encode();
+        let mut parser = AnsiParser::new();
+        parser.set_window_op_policy(|_| true);
+        assert_eq!(parser.push(&encoded), vec![item], "encoded as {:?}", String::from_utf8_lossy(&encoded));
+    }
+}
+
+#[test]
+fn test_encode_canonical_sequences() {
+    assert_eq!(TerminalOutput::CursorUp(3).encode(), b"\x1B[3A");
+    assert_eq!(TerminalOutput::SetCursorPos { x: Some(20), y: Some(10) }.encode(), b"\x1B[10;20H");
+    assert_eq!(TerminalOutput::Sgr(SelectGraphicRendition::ForegroundTrueColor(1, 2, 3)).encode(), b"\x1B[38;2;1;2;3m");
+    assert_eq!(TerminalOutput::SetMode(Mode::BracketedPaste).encode(), b"\x1B[?2004h");
+    assert_eq!(TerminalOutput::Osc { command: 2, payload: b"title".to_vec() }.encode(), b"\x1B]2;title\x1B\\");
+    assert_eq!(
+        TerminalOutput::SetCursorStyle { shape: CursorShape::Beam, blinking: false }.encode(),
+        b"\x1B[6 q"
+    );
+    assert_eq!(TerminalOutput::Invalid.encode(), b"");
+}
+
+#[test]
+fn test_sgr_encode() {
+    assert_eq!(SelectGraphicRendition::Reset.encode(), b"\x1B[0m");
+    assert_eq!(SelectGraphicRendition::Background8Bit(200).params(), "48;5;200");
+}
+
+#[test]
+fn test_write_to_stream() {
+    let items = vec![
+        TerminalOutput::Sgr(SelectGraphicRendition::Bold),
+        TerminalOutput::Data(b"hello".to_vec()),
+        TerminalOutput::CarriageReturn,
+        TerminalOutput::Newline,
+        TerminalOutput::SetCursorPos { x: None, y: Some(4) },
+        TerminalOutput::Data(b"world".to_vec()),
+    ];
+    let mut stream = Vec::new();
+    for item in &items {
+        item.write_to(&mut stream).unwrap();
+    }
+
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(&stream), items);
+}
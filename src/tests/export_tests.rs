@@ -0,0 +1,110 @@
+use crate::definitions::*;
+use crate::export::*;
+
+#[test]
+fn test_styled_text_tags() {
+    let styled = StyledText::from_bytes(b"plain \x1B[1;31mred\x1B[0m done");
+    assert_eq!(styled.text, "plain red done");
+    assert_eq!(styled.tags, vec![FormatTag {
+        start: 6,
+        end: 9,
+        blink: false,
+        fg_color: SelectGraphicRendition::ForegroundRed,
+        bg_color: SelectGraphicRendition::BackgroundDefault,
+        bold: true,
+        italic: false,
+        underline: false,
+        url: None,
+    }]);
+}
+
+#[test]
+fn test_styled_text_reverse_swaps_colors() {
+    let styled = StyledText::from_bytes(b"\x1B[32;7mX");
+    assert_eq!(styled.tags[0].fg_color, SelectGraphicRendition::BackgroundDefault);
+    assert_eq!(styled.tags[0].bg_color, SelectGraphicRendition::ForegroundGreen);
+}
+
+#[test]
+fn test_styled_text_carriage_return_overwrites() {
+    let styled = StyledText::from_bytes(b"[    ] 0%\r[====] 100%\r\nnext");
+    assert_eq!(styled.text, "[====] 100%\nnext");
+}
+
+#[test]
+fn test_html_inline_styles() {
+    let styled = StyledText::from_bytes(b"a\x1B[4;38;2;1;2;3m<b>\x1B[0m");
+    let html = styled.to_html(&HtmlOptions::default());
+    assert_eq!(
+        html,
+        "<pre style=\"color:#e5e5e5;background-color:#000000\">a\
+         <span style=\"color:#010203;text-decoration:underline\">&lt;b&gt;</span></pre>"
+    );
+}
+
+#[test]
+fn test_html_classes_and_palette() {
+    let mut palette = Palette::default();
+    palette.ansi[4] = Rgb::new(0x11, 0x22, 0x33);
+    let options = HtmlOptions {
+        palette,
+        style_mode: StyleMode::Classes,
+        class_prefix: "ansi".to_string(),
+        full_document: true,
+    };
+    let html = StyledText::from_bytes(b"\x1B[1;34mblue\x1B[m").to_html(&options);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(".ansi-fg-4{color:#112233}"));
+    assert!(html.contains("<pre class=\"ansi\"><span class=\"ansi-fg-4 ansi-bold\">blue</span></pre>"));
+}
+
+#[test]
+fn test_html_hyperlink() {
+    let styled = StyledText::from_bytes(b"see \x1B]8;;https://example.com/?a=1&b=2\x07here\x1B]8;;\x07.");
+    let html = styled.to_html(&HtmlOptions::default());
+    assert!(html.contains("see <a href=\"https://example.com/?a=1&amp;b=2\">here</a>."));
+}
+
+#[test]
+fn test_disallowed_hyperlink_is_plain_text() {
+    let styled = StyledText::from_bytes(b"\x1B]8;;javascript:alert(1)\x07click\x1B]8;;\x07 \x1B]8;;HTTP://a\x07ok\x1B]8;;\x07");
+    let html = styled.to_html(&HtmlOptions::default());
+    assert!(!html.contains("javascript"));
+    assert!(html.contains("click <a href=\"HTTP://a\">ok</a>"));
+
+    let svg = styled.to_svg(&SvgOptions::default());
+    assert!(!svg.contains("javascript"));
+    assert!(svg.contains("<a href=\"HTTP://a\">"));
+}
+
+#[test]
+fn test_svg_grid() {
+    let styled = StyledText::from_bytes(b"ab\n\x1B[41mxyz\x1B[0m");
+    let svg = styled.to_svg(&SvgOptions::default());
+
+    // 3 columns x 2 rows, 8px padding, 9x18 cells
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"43\" height=\"52\""));
+    assert!(svg.contains("<text x=\"8\" y=\"22\" xml:space=\"preserve\">ab</text>"));
+    assert!(svg.contains("<rect x=\"8\" y=\"26\" width=\"27\" height=\"18\" fill=\"#cd0000\"/>"));
+    assert!(svg.contains("<text x=\"8\" y=\"40\" xml:space=\"preserve\">xyz</text>"));
+}
+
+#[test]
+fn test_export_is_deterministic() {
+    let input = b"\x1B[1mone\x1B[0m\r\n\x1B[38;5;200mtwo\x1B[0m\r\n";
+    let options = SvgOptions::default();
+    assert_eq!(
+        StyledText::from_bytes(input).to_svg(&options),
+        StyledText::from_bytes(input).to_svg(&options)
+    );
+}
+
+#[test]
+fn test_huge_cursor_forward_is_clamped() {
+    let styled = StyledText::from_bytes(b"\x1B[99999999999999999999999Cx");
+    assert_eq!(styled.text, format!("{}x", " ".repeat(4096)));
+
+    let styled = StyledText::from_bytes(b"ab\x1B[100000000Cx\x1B[5Cy");
+    assert_eq!(styled.text.len(), 4098);
+}
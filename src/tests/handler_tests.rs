@@ -0,0 +1,128 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::handlers::*;
+
+#[derive(Debug, PartialEq)]
+struct EditorCommand {
+    action: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Graphics(Vec<u8>);
+
+#[test]
+fn test_custom_osc_handler() {
+    let mut parser = AnsiParser::new();
+    parser.register_osc_handler(5001, |params, _| {
+        let action = String::from_utf8(params?.to_vec()).ok()?;
+        Some(HandlerResponse::extension(EditorCommand { action }))
+    });
+
+    let output = parser.push(b"a\x1B]5001;open\x07b");
+    assert_eq!(output.len(), 3);
+    match &output[1] {
+        TerminalOutput::Extension(event) => {
+            assert_eq!(event.downcast_ref::<EditorCommand>(), Some(&EditorCommand { action: "open".into() }));
+            assert!(!event.is::<Graphics>());
+        }
+        other => panic!("Expected Extension, got {:?}", other),
+    }
+
+    // The handler declines strings without a payload, which are reported raw
+    let output = parser.push(b"\x1B]5001\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 5001, payload: b"5001".to_vec() }]);
+
+    // Other numbers are unaffected
+    let output = parser.push(b"\x1B]5002;x\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 5002, payload: b"x".to_vec() }]);
+}
+
+#[test]
+fn test_extension_events_compare_by_value() {
+    let a: Box<dyn ExtensionEvent> = Box::new(EditorCommand { action: "a".into() });
+    let b: Box<dyn ExtensionEvent> = Box::new(EditorCommand { action: "a".into() });
+    let c: Box<dyn ExtensionEvent> = Box::new(Graphics(b"a".to_vec()));
+    assert_eq!(TerminalOutput::Extension(a), TerminalOutput::Extension(b));
+    assert_ne!(
+        TerminalOutput::Extension(Box::new(EditorCommand { action: "a".into() })),
+        TerminalOutput::Extension(c)
+    );
+}
+
+#[test]
+fn test_handler_replies() {
+    let mut parser = AnsiParser::new();
+    parser.register_osc_handler(5100, |_, terminator| {
+        let mut reply = b"\x1B]5100;pong".to_vec();
+        reply.extend_from_slice(terminator.as_bytes());
+        Some(HandlerResponse::default().with_reply(reply))
+    });
+
+    let output = parser.push(b"\x1B]5100;ping\x07\x1B]5100;ping\x1B\\");
+    assert!(output.is_empty());
+    assert_eq!(parser.take_replies(), b"\x1B]5100;pong\x07\x1B]5100;pong\x1B\\".to_vec());
+    assert!(parser.take_replies().is_empty());
+}
+
+#[test]
+fn test_override_builtin_osc_handler() {
+    let mut parser = AnsiParser::new();
+    parser.register_osc_handler(8, |_, _| Some(HandlerResponse::default()));
+    assert!(parser.push(b"\x1B]8;;https://example.com\x07").is_empty());
+}
+
+#[test]
+fn test_custom_dcs_handler() {
+    let mut parser = AnsiParser::new();
+    parser.register_dcs_handler(b"+", b'q', |params, data| {
+        assert_eq!(params, &[1]);
+        Some(HandlerResponse::extension(Graphics(data.to_vec())).with_reply(&b"\x1BP1+r\x1B\\"[..]))
+    });
+
+    let output = parser.push(b"\x1BP1+q544e\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::Extension(Box::new(Graphics(b"544e".to_vec())))]);
+    assert_eq!(parser.take_replies(), b"\x1BP1+r\x1B\\".to_vec());
+
+    // Same final byte without the intermediate is still reported raw
+    let output = parser.push(b"\x1BP1q#0\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::DeviceControlString {
+        params: vec![1],
+        intermediates: vec![],
+        data: b"q#0".to_vec(),
+    }]);
+}
+
+#[test]
+fn test_custom_apc_handler() {
+    let mut parser = AnsiParser::new();
+    parser.register_apc_handler(b"G", |payload| Some(HandlerResponse::extension(Graphics(payload.to_vec()))));
+    parser.register_apc_handler(b"Gx", |_| Some(HandlerResponse::default()));
+
+    let output = parser.push(b"a\x1B_Gf=100;AAAA\x1B\\b");
+    assert_eq!(output, vec![
+        TerminalOutput::Data(b"a".to_vec()),
+        TerminalOutput::Extension(Box::new(Graphics(b"f=100;AAAA".to_vec()))),
+        TerminalOutput::Data(b"b".to_vec()),
+    ]);
+
+    // The longest matching prefix wins
+    assert!(parser.push(b"\x1B_Gxyz\x1B\\").is_empty());
+
+    // Unregistered APC strings are still dropped
+    let output = parser.push(b"\x1B_other\x1B\\c");
+    assert_eq!(output, vec![TerminalOutput::Data(b"c".to_vec())]);
+}
+
+#[test]
+fn test_handler_output_respects_synchronized_update() {
+    let mut parser = AnsiParser::new();
+    parser.register_apc_handler(b"G", |payload| Some(HandlerResponse::extension(Graphics(payload.to_vec()))));
+
+    let output = parser.push(b"\x1B[?2026h\x1B_G1\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::BeginSynchronizedUpdate]);
+    let output = parser.push(b"\x1B[?2026l");
+    assert_eq!(output, vec![
+        TerminalOutput::Extension(Box::new(Graphics(b"1".to_vec()))),
+        TerminalOutput::EndSynchronizedUpdate,
+    ]);
+}
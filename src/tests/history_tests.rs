@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::history::*;
+
+#[test]
+fn test_osc133_marks() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]133;A\x07$ \x1B]133;B\x07ls\x1B]133;C\x07\x1B]133;D;0\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::PromptStart,
+        TerminalOutput::Data(b"$ ".to_vec()),
+        TerminalOutput::CommandStart,
+        TerminalOutput::Data(b"ls".to_vec()),
+        TerminalOutput::CommandExecuted,
+        TerminalOutput::CommandFinished { exit_code: Some(0) },
+    ]);
+
+    // Options after the mark are ignored, and D may omit the exit status
+    let output = parser.push(b"\x1B]133;A;aid=42;cl=m\x1B\\\x1B]133;D\x1B\\\x1B]133;D;130\x1B\\");
+    assert_eq!(output, vec![
+        TerminalOutput::PromptStart,
+        TerminalOutput::CommandFinished { exit_code: None },
+        TerminalOutput::CommandFinished { exit_code: Some(130) },
+    ]);
+
+    // Marks we don't know about are reported raw
+    let output = parser.push(b"\x1B]133;P;k=i\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 133, payload: b"P;k=i".to_vec() }]);
+}
+
+#[test]
+fn test_osc633_marks() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]633;A\x07\x1B]633;B\x07\x1B]633;C\x07\x1B]633;D;1\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::PromptStart,
+        TerminalOutput::CommandStart,
+        TerminalOutput::CommandExecuted,
+        TerminalOutput::CommandFinished { exit_code: Some(1) },
+    ]);
+}
+
+#[test]
+fn test_history_groups_commands() {
+    let mut parser = AnsiParser::new();
+    let mut history = CommandHistory::new();
+    let t0 = Instant::now();
+    let t1 = t0 + Duration::from_millis(250);
+
+    history.extend(&parser.push(b"motd\n\x1B]133;A\x07$ \x1B]133;B\x07ls\n\x1B]133;C\x07"), t0);
+    history.extend(&parser.push(b"a.txt\nb.txt\n\x1B]133;D;0\x07"), t1);
+    history.extend(&parser.push(b"\x1B]133;A\x07$ \x1B]133;B\x07false\n\x1B]133;C\x07\x1B]133;D;1\x07"), t1);
+
+    let blocks = history.blocks();
+    assert_eq!(blocks.len(), 2);
+
+    let first = &blocks[0];
+    assert_eq!(first.start, 5);
+    assert_eq!(first.prompt, b"$ ");
+    assert_eq!(first.command, b"ls\n");
+    assert_eq!(first.output, 10..22);
+    assert_eq!(first.output_text(), b"a.txt\nb.txt\n");
+    assert_eq!(first.exit_code, Some(0));
+    assert_eq!(first.duration(), Some(Duration::from_millis(250)));
+
+    let second = &blocks[1];
+    assert_eq!(second.command, b"false\n");
+    assert_eq!(second.output, 30..30);
+    assert_eq!(second.exit_code, Some(1));
+
+    assert_eq!(history.last_output(), Some(&b""[..]));
+    assert_eq!(history.previous(history.offset()).map(|b| b.start), Some(22));
+    assert_eq!(history.previous(22).map(|b| b.start), Some(5));
+    assert_eq!(history.previous(5), None);
+    assert_eq!(history.next(5).map(|b| b.start), Some(22));
+}
+
+#[test]
+fn test_history_in_progress_and_abandoned_blocks() {
+    let mut history = CommandHistory::new();
+    let now = Instant::now();
+
+    // A `D` before any prompt is ignored
+    history.record(&TerminalOutput::CommandFinished { exit_code: Some(0) }, now);
+    assert!(history.blocks().is_empty());
+
+    history.record(&TerminalOutput::PromptStart, now);
+    history.record(&TerminalOutput::Data(b"$ ".to_vec()), now);
+    history.record(&TerminalOutput::CommandStart, now);
+    assert_eq!(history.current().map(|b| b.prompt.as_slice()), Some(&b"$ "[..]));
+
+    // A new prompt without `D` (e.g. Ctrl-C) keeps the old block unfinished
+    history.record(&TerminalOutput::PromptStart, now);
+    assert_eq!(history.blocks().len(), 1);
+    assert!(!history.blocks()[0].is_finished());
+    assert_eq!(history.last_output(), None);
+
+    // Marks without a preceding `A` still open a block
+    let mut history = CommandHistory::new();
+    history.record(&TerminalOutput::CommandExecuted, now);
+    history.record(&TerminalOutput::Data(b"out".to_vec()), now);
+    history.record(&TerminalOutput::CommandFinished { exit_code: None }, now);
+    assert_eq!(history.last_output(), Some(&b"out"[..]));
+}
+
+#[test]
+fn test_history_limit() {
+    let mut history = CommandHistory::new();
+    let now = Instant::now();
+    for i in 0..5 {
+        history.record(&TerminalOutput::PromptStart, now);
+        history.record(&TerminalOutput::CommandExecuted, now);
+        history.record(&TerminalOutput::Data(vec![b'0' + i]), now);
+        history.record(&TerminalOutput::CommandFinished { exit_code: Some(0) }, now);
+    }
+    history.set_max_blocks(2);
+    assert_eq!(history.blocks().len(), 2);
+    assert_eq!(history.blocks()[0].output_text(), b"3");
+    assert_eq!(history.last_output(), Some(&b"4"[..]));
+}
@@ -0,0 +1,78 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+#[test]
+fn test_switch_to_latin1() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B%@caf\xE9 \xA3\x1B%Gcaf\xC3\xA9");
+    assert_eq!(output, vec![
+        TerminalOutput::SetInputEncoding(InputEncoding::SingleByte),
+        TerminalOutput::Data("café £".as_bytes().to_vec()),
+        TerminalOutput::SetInputEncoding(InputEncoding::Utf8),
+        TerminalOutput::Data("café".as_bytes().to_vec()),
+    ]);
+}
+
+#[test]
+fn test_input_encoding_api() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.input_encoding(), InputEncoding::Utf8);
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    assert_eq!(parser.push(b"\xC3\xA9"), vec![TerminalOutput::Data("Ã©".as_bytes().to_vec())]);
+    parser.set_input_encoding(InputEncoding::Utf8);
+    assert_eq!(parser.push(b"\xC3\xA9"), vec![TerminalOutput::Data("é".as_bytes().to_vec())]);
+}
+
+#[test]
+fn test_code_page() {
+    // Code page 437 box drawing
+    let mut parser = AnsiParser::new();
+    parser.set_code_page(|byte| match byte {
+        0xC4 => '─',
+        0xB3 => '│',
+        _ => '?',
+    });
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    assert_eq!(parser.push(b"\xC4\xB3\xFF"), vec![TerminalOutput::Data("─│?".as_bytes().to_vec())]);
+}
+
+#[test]
+fn test_single_byte_c1_controls() {
+    let mut parser = AnsiParser::new();
+    parser.set_c1_controls(true);
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    // After a Latin-1 letter, 0x9B is still CSI
+    let output = parser.push(b"\xC3\x9B1m\xE9");
+    assert_eq!(output, vec![
+        TerminalOutput::Data("Ã".as_bytes().to_vec()),
+        TerminalOutput::Sgr(SelectGraphicRendition::Bold),
+        TerminalOutput::Data("é".as_bytes().to_vec()),
+    ]);
+}
+
+#[test]
+fn test_single_byte_gr_charset() {
+    let mut parser = AnsiParser::new();
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    let output = parser.push(b"\x1B*%5\xA8\xD7");
+    assert_eq!(output, vec![
+        TerminalOutput::ConfigureCharset { index: CharsetIndex::G2, charset: StandardCharset::DecSupplemental },
+        TerminalOutput::Data("¤Œ".as_bytes().to_vec()),
+    ]);
+}
+
+#[test]
+fn test_input_encoding_round_trip() {
+    assert_eq!(TerminalOutput::SetInputEncoding(InputEncoding::SingleByte).encode(), b"\x1B%@");
+    assert_eq!(TerminalOutput::SetInputEncoding(InputEncoding::Utf8).encode(), b"\x1B%G");
+}
+
+#[test]
+fn test_escape_switch_flushes_partial_character() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"a\xE2\x9C\x1B%@\xE9"), vec![
+        TerminalOutput::Data(b"a\xE2\x9C".to_vec()),
+        TerminalOutput::SetInputEncoding(InputEncoding::SingleByte),
+        TerminalOutput::Data("é".as_bytes().to_vec()),
+    ]);
+}
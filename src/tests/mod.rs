@@ -1,2 +1,42 @@
 #[cfg(test)]
-mod ansi_parser_tests;
\ No newline at end of file
+mod ansi_parser_tests;
+#[cfg(test)]
+mod export_tests;
+#[cfg(test)]
+mod sanitize_tests;
+#[cfg(test)]
+mod encode_tests;
+#[cfg(test)]
+mod color_tests;
+#[cfg(test)]
+mod clipboard_tests;
+#[cfg(test)]
+mod handler_tests;
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod osc_tests;
+#[cfg(test)]
+mod notify_tests;
+#[cfg(test)]
+mod title_tests;
+#[cfg(test)]
+mod window_tests;
+#[cfg(test)]
+mod c1_tests;
+#[cfg(test)]
+mod vt52_tests;
+#[cfg(test)]
+mod charset_tests;
+#[cfg(test)]
+mod input_encoding_tests;
+#[cfg(test)]
+mod rectangle_tests;
+#[cfg(test)]
+mod screen_tests;
+#[cfg(test)]
+mod protection_tests;
+#[cfg(test)]
+mod reset_tests;
+#[cfg(test)]
+mod c0_tests;
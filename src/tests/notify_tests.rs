@@ -0,0 +1,88 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::notify::MAX_NOTIFICATION_LEN;
+
+fn notification(title: Option<&str>, body: &str, id: Option<&str>, urgency: Urgency) -> TerminalOutput {
+    TerminalOutput::Notification {
+        title: title.map(str::to_string),
+        body: body.to_string(),
+        id: id.map(str::to_string),
+        urgency,
+    }
+}
+
+#[test]
+fn test_osc9_notification() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]9;Build finished; 3 warnings\x07");
+    assert_eq!(output, vec![notification(None, "Build finished; 3 warnings", None, Urgency::Normal)]);
+
+    // A bare number is still a message
+    let output = parser.push(b"\x1B]9;42\x07");
+    assert_eq!(output, vec![notification(None, "42", None, Urgency::Normal)]);
+
+    // Other ConEmu commands are left raw
+    let output = parser.push(b"\x1B]9;9;C:\\\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 9, payload: b"9;C:\\".to_vec() }]);
+}
+
+#[test]
+fn test_osc9_progress() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]9;4;1;42\x1B\\\x1B]9;4;3\x1B\\\x1B]9;4;2;250\x1B\\\x1B]9;4;0;0\x1B\\\x1B]9;4\x07");
+    assert_eq!(output, vec![
+        TerminalOutput::Progress { state: ProgressState::Normal, percent: Some(42) },
+        TerminalOutput::Progress { state: ProgressState::Indeterminate, percent: None },
+        TerminalOutput::Progress { state: ProgressState::Error, percent: Some(100) },
+        TerminalOutput::Progress { state: ProgressState::Hidden, percent: Some(0) },
+        // Without a `;` this is a notification reading "4"
+        notification(None, "4", None, Urgency::Normal),
+    ]);
+
+    let output = parser.push(b"\x1B]9;4;7;10\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 9, payload: b"4;7;10".to_vec() }]);
+}
+
+#[test]
+fn test_osc777_notification() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]777;notify;make;done; all targets built\x07");
+    assert_eq!(output, vec![notification(Some("make"), "done; all targets built", None, Urgency::Normal)]);
+
+    let output = parser.push(b"\x1B]777;notify;;body only\x07\x1B]777;preexec\x07");
+    assert_eq!(output, vec![
+        notification(None, "body only", None, Urgency::Normal),
+        TerminalOutput::Osc { command: 777, payload: b"preexec".to_vec() },
+    ]);
+}
+
+#[test]
+fn test_osc99_notification() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]99;;Hello world\x1B\\");
+    assert_eq!(output, vec![notification(Some("Hello world"), "", None, Urgency::Normal)]);
+
+    // Title and body sent as chunks with an id, urgency on any chunk
+    assert!(parser.push(b"\x1B]99;i=1:d=0;Tests\x1B\\").is_empty());
+    assert!(parser.push(b"\x1B]99;i=2:d=0:u=2;Other\x1B\\").is_empty());
+    let output = parser.push(b"\x1B]99;i=1:d=0:u=2:p=body;12 \x1B\\\x1B]99;i=1:p=body;failed\x1B\\");
+    assert_eq!(output, vec![notification(Some("Tests"), "12 failed", Some("1"), Urgency::Critical)]);
+
+    let output = parser.push(b"\x1B]99;i=2:p=body:e=1;Ym9keQ==\x1B\\");
+    assert_eq!(output, vec![notification(Some("Other"), "body", Some("2"), Urgency::Critical)]);
+}
+
+#[test]
+fn test_osc99_unsupported_and_oversized() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]99;i=1:p=?;\x1B\\");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 99, payload: b"i=1:p=?;".to_vec() }]);
+
+    let mut sequence = b"\x1B]99;i=big:d=0;".to_vec();
+    sequence.extend(std::iter::repeat_n(b'x', MAX_NOTIFICATION_LEN + 1));
+    sequence.extend_from_slice(b"\x1B\\");
+    assert!(parser.push(&sequence).is_empty());
+    // The oversized notification was discarded, so this starts afresh
+    let output = parser.push(b"\x1B]99;i=big;small\x1B\\");
+    assert_eq!(output, vec![notification(Some("small"), "", Some("big"), Urgency::Normal)]);
+}
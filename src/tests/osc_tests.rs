@@ -0,0 +1,56 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::osc::*;
+
+fn cwd(host: Option<&str>, path: &[u8]) -> TerminalOutput {
+    TerminalOutput::WorkingDirectory { host: host.map(str::to_string), path: path.to_vec() }
+}
+
+#[test]
+fn test_percent_decoding() {
+    assert_eq!(percent_decode(b"/a%20b/%E2%9C%93"), "/a b/✓".as_bytes());
+    assert_eq!(percent_decode(b"%ff%FE"), vec![0xFF, 0xFE]);
+    // Malformed escapes are left alone
+    assert_eq!(percent_decode(b"100%/%4/%zz%"), b"100%/%4/%zz%");
+    assert_eq!(percent_encode(b"/tmp/a b%"), "/tmp/a%20b%25");
+}
+
+#[test]
+fn test_osc7_working_directory() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]7;file://myhost/home/user/My%20Docs\x1B\\");
+    assert_eq!(output, vec![cwd(Some("myhost"), b"/home/user/My Docs")]);
+
+    // No host, BEL terminator, scheme is case-insensitive
+    let output = parser.push(b"\x1B]7;FILE:///tmp\x07");
+    assert_eq!(output, vec![cwd(None, b"/tmp")]);
+
+    // Non-UTF-8 paths survive as bytes
+    let output = parser.push(b"\x1B]7;file://h/caf%E9\x07");
+    assert_eq!(output, vec![cwd(Some("h"), b"/caf\xE9")]);
+
+    // kitty sends the path unencoded
+    let output = parser.push(b"\x1B]7;kitty-shell-cwd://h/a%20b\x07");
+    assert_eq!(output, vec![cwd(Some("h"), b"/a%20b")]);
+}
+
+#[test]
+fn test_osc7_rejects_other_urls() {
+    let mut parser = AnsiParser::new();
+    for url in [&b"http://host/path"[..], b"file:relative", b"file://hostonly", b"/just/a/path"] {
+        let mut sequence = b"\x1B]7;".to_vec();
+        sequence.extend_from_slice(url);
+        sequence.push(0x07);
+        assert_eq!(parser.push(&sequence), vec![TerminalOutput::Osc { command: 7, payload: url.to_vec() }]);
+    }
+}
+
+#[test]
+fn test_osc1337_current_dir() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]1337;CurrentDir=/Users/me/a%b\x07");
+    assert_eq!(output, vec![cwd(None, b"/Users/me/a%b")]);
+
+    let output = parser.push(b"\x1B]1337;SetMark\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 1337, payload: b"SetMark".to_vec() }]);
+}
@@ -0,0 +1,37 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+#[test]
+fn test_decsca() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[1\"q"), vec![TerminalOutput::SetCharacterProtection(true)]);
+    assert_eq!(parser.push(b"\x1B[\"q"), vec![TerminalOutput::SetCharacterProtection(false)]);
+    assert_eq!(parser.push(b"\x1B[2\"q"), vec![TerminalOutput::SetCharacterProtection(false)]);
+    assert_eq!(parser.push(b"\x1B[3\"q"), vec![]);
+}
+
+#[test]
+fn test_guarded_area() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1BVa\x1BW"), vec![
+        TerminalOutput::StartGuardedArea,
+        TerminalOutput::Data(b"a".to_vec()),
+        TerminalOutput::EndGuardedArea,
+    ]);
+    // 8-bit SPA and EPA
+    parser.set_c1_controls(true);
+    assert_eq!(parser.push(b"\x96\x97"), vec![TerminalOutput::StartGuardedArea, TerminalOutput::EndGuardedArea]);
+}
+
+#[test]
+fn test_selective_erase() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[?J\x1B[?1J\x1B[?2J\x1B[?0K\x1B[?1K\x1B[?2K\x1B[?3K"), vec![
+        TerminalOutput::SelectiveEraseDisplay(EraseRange::Forwards),
+        TerminalOutput::SelectiveEraseDisplay(EraseRange::Backwards),
+        TerminalOutput::SelectiveEraseDisplay(EraseRange::All),
+        TerminalOutput::SelectiveEraseLine(EraseRange::Forwards),
+        TerminalOutput::SelectiveEraseLine(EraseRange::Backwards),
+        TerminalOutput::SelectiveEraseLine(EraseRange::All),
+    ]);
+}
@@ -0,0 +1,76 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+fn rect(top: usize, left: usize, bottom: Option<usize>, right: Option<usize>) -> Rect {
+    Rect { top, left, bottom, right }
+}
+
+#[test]
+fn test_rectangle_defaults() {
+    let mut parser = AnsiParser::new();
+    // Omitted and zero coordinates take the defaults: the whole page
+    assert_eq!(parser.push(b"\x1B[$z"), vec![TerminalOutput::EraseRectangle(rect(1, 1, None, None))]);
+    assert_eq!(parser.push(b"\x1B[0;0;0;0$z"), vec![TerminalOutput::EraseRectangle(rect(1, 1, None, None))]);
+    assert_eq!(parser.push(b"\x1B[2;3;4;5${"), vec![TerminalOutput::SelectiveEraseRectangle(rect(2, 3, Some(4), Some(5)))]);
+}
+
+#[test]
+fn test_copy_rectangle() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[1;1;2;10;1;5;20;1$v"), vec![TerminalOutput::CopyRectangle {
+        source: rect(1, 1, Some(2), Some(10)),
+        top: 5,
+        left: 20,
+    }]);
+    // The destination defaults to the top left corner
+    assert_eq!(parser.push(b"\x1B[3;4$v"), vec![TerminalOutput::CopyRectangle {
+        source: rect(3, 4, None, None),
+        top: 1,
+        left: 1,
+    }]);
+}
+
+#[test]
+fn test_fill_rectangle() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[42;2;2;3;3$x"), vec![TerminalOutput::FillRectangle {
+        ch: '*',
+        area: rect(2, 2, Some(3), Some(3)),
+    }]);
+    assert_eq!(parser.push(b"\x1B[233$x"), vec![TerminalOutput::FillRectangle { ch: 'é', area: rect(1, 1, None, None) }]);
+    // Control characters can't be used to fill
+    assert_eq!(parser.push(b"\x1B[10;1;1;2;2$x"), vec![]);
+}
+
+#[test]
+fn test_change_and_reverse_attributes() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[1;1;2;2;1;4;31$r"), vec![TerminalOutput::ChangeRectangleAttributes {
+        area: rect(1, 1, Some(2), Some(2)),
+        attributes: vec![SelectGraphicRendition::Bold, SelectGraphicRendition::Underline],
+    }]);
+    assert_eq!(parser.push(b"\x1B[1;1;2;2$t"), vec![TerminalOutput::ReverseRectangleAttributes {
+        area: rect(1, 1, Some(2), Some(2)),
+        attributes: vec![SelectGraphicRendition::Reset],
+    }]);
+    // DECRARA can't turn attributes off
+    assert_eq!(parser.push(b"\x1B[;;;;7;27$t"), vec![TerminalOutput::ReverseRectangleAttributes {
+        area: rect(1, 1, None, None),
+        attributes: vec![SelectGraphicRendition::Reverse],
+    }]);
+}
+
+#[test]
+fn test_request_checksum() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[7;1;2;3;4;5*y"), vec![TerminalOutput::RequestChecksum {
+        id: 7,
+        page: 1,
+        area: rect(2, 3, Some(4), Some(5)),
+    }]);
+    assert_eq!(parser.push(b"\x1B[*y"), vec![TerminalOutput::RequestChecksum {
+        id: 0,
+        page: 1,
+        area: rect(1, 1, None, None),
+    }]);
+}
@@ -0,0 +1,85 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+#[test]
+fn test_full_reset_restores_charsets() {
+    let mut parser = AnsiParser::new();
+    parser.push(b"\x1B(0\x1B[20h");
+    assert_eq!(parser.push(b"q\x1Bcq\n"), vec![
+        TerminalOutput::Data("─".as_bytes().to_vec()),
+        TerminalOutput::FullReset,
+        TerminalOutput::Data(b"q".to_vec()),
+        TerminalOutput::Newline,
+    ]);
+}
+
+#[test]
+fn test_soft_reset_restores_charsets_only() {
+    let mut parser = AnsiParser::new();
+    parser.push(b"\x1B)0\x0E\x1B[20h");
+    assert_eq!(parser.push(b"\x1B[!pq\n"), vec![
+        TerminalOutput::SoftReset,
+        TerminalOutput::Data(b"q".to_vec()),
+        // LNM isn't part of DECSTR
+        TerminalOutput::Newline,
+        TerminalOutput::CarriageReturn,
+    ]);
+}
+
+#[test]
+fn test_reset_abandons_partial_sequences() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[3"), vec![]);
+    assert_eq!(parser.reset(), vec![]);
+    assert_eq!(parser.push(b"1mx"), vec![TerminalOutput::Data(b"1mx".to_vec())]);
+
+    assert_eq!(parser.push(b"\x1B]2;unfinished"), vec![]);
+    parser.reset();
+    assert_eq!(parser.push(b"x\x07"), vec![TerminalOutput::Data(b"x".to_vec()), TerminalOutput::Bell]);
+
+    // A partial UTF-8 character is dropped rather than flushed
+    assert_eq!(parser.push(b"\xE2\x9C"), vec![]);
+    parser.reset();
+    assert_eq!(parser.push(b"a"), vec![TerminalOutput::Data(b"a".to_vec())]);
+}
+
+#[test]
+fn test_reset_leaves_vt52_mode() {
+    let mut parser = AnsiParser::new();
+    parser.set_vt52_mode(true);
+    parser.reset();
+    assert!(!parser.is_vt52_mode());
+}
+
+#[test]
+fn test_reset_keeps_configuration() {
+    let mut parser = AnsiParser::new();
+    parser.set_c1_controls(true);
+    parser.set_input_encoding(InputEncoding::SingleByte);
+    parser.reset();
+    assert_eq!(parser.input_encoding(), InputEncoding::SingleByte);
+    assert_eq!(parser.push(b"\x9B1m"), vec![TerminalOutput::Sgr(SelectGraphicRendition::Bold)]);
+}
+
+#[test]
+fn test_reset_flushes_synchronized_update() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[?2026h\x1B[1m"), vec![TerminalOutput::BeginSynchronizedUpdate]);
+    assert_eq!(parser.push(b"\x1Bc"), vec![
+        TerminalOutput::Sgr(SelectGraphicRendition::Bold),
+        TerminalOutput::EndSynchronizedUpdate,
+        TerminalOutput::FullReset,
+    ]);
+    // The update is over
+    assert_eq!(parser.push(b"\x1B[1m"), vec![TerminalOutput::Sgr(SelectGraphicRendition::Bold)]);
+}
+
+#[test]
+fn test_reset_discards_synchronized_update() {
+    let mut parser = AnsiParser::new();
+    parser.set_sync_reset_policy(SyncResetPolicy::Discard);
+    parser.push(b"\x1B[?2026h\x1B[1m");
+    assert_eq!(parser.reset(), vec![TerminalOutput::EndSynchronizedUpdate]);
+    // Nothing to end without an update in progress
+    assert_eq!(parser.reset(), vec![]);
+}
@@ -0,0 +1,95 @@
+use crate::sanitize::*;
+
+#[test]
+fn test_sanitizer_keeps_colors() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let clean = sanitizer.push(b"\x1B[1;31mred\x1B[0m\r\n\x1B[38;5;42mx\x1B[48;2;1;2;3my");
+    assert_eq!(
+        clean,
+        b"\x1B[1m\x1B[31mred\x1B[0m\r\n\x1B[38;5;42mx\x1B[48;2;1;2;3my".to_vec()
+    );
+}
+
+#[test]
+fn test_sanitizer_drops_dangerous_sequences() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let clean = sanitizer.push(
+        b"a\x1B]52;c;ZXZpbA==\x07b\x1B]0;pwned\x07c\x1BP$qm\x1B\\d\x1B[2J\x1B[H\x1B[?1049he",
+    );
+    assert_eq!(clean, b"abcde".to_vec());
+}
+
+#[test]
+fn test_sanitizer_hyperlink_schemes() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let clean = sanitizer.push(b"\x1B]8;id=x;https://example.com\x07ok\x1B]8;;\x07");
+    assert_eq!(clean, b"\x1B]8;id=x;https://example.com\x1B\\ok\x1B]8;;\x1B\\".to_vec());
+
+    // Rejected scheme: text is kept, the link and its close are not
+    let clean = sanitizer.push(b"\x1B]8;;javascript:alert(1)\x07bad\x1B]8;;\x07");
+    assert_eq!(clean, b"bad".to_vec());
+}
+
+#[test]
+fn test_sanitizer_placeholders() {
+    let policy = SanitizePolicy {
+        line_controls: false,
+        placeholders: true,
+        ..SanitizePolicy::text_only()
+    };
+    let mut sanitizer = Sanitizer::new(policy);
+    let clean = sanitizer.push(b"a\x1B[31m\nb\x0Ec\x11");
+    assert_eq!(String::from_utf8(clean).unwrap(), "a\u{240A}b\u{240E}c\u{2411}");
+
+    let clean = sanitizer.push(b"\t\x07\x05");
+    assert_eq!(String::from_utf8(clean).unwrap(), "\u{2409}\u{2407}\u{2405}");
+}
+
+#[test]
+fn test_sanitizer_strips_c1_and_invalid_utf8() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::text_only());
+    let clean = sanitizer.push("x\u{9B}y".as_bytes());
+    assert_eq!(clean, b"xy".to_vec());
+
+    let clean = sanitizer.push(b"x\x9B31my");
+    assert_eq!(String::from_utf8(clean).unwrap(), "x\u{FFFD}31my");
+}
+
+#[test]
+fn test_sanitizer_split_input() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let mut clean = sanitizer.push(b"\x1B]0;ti");
+    clean.extend(sanitizer.push(b"tle\x07\x1B[3"));
+    clean.extend(sanitizer.push(b"2mok"));
+    assert_eq!(clean, b"\x1B[32mok".to_vec());
+}
+
+#[test]
+fn test_sanitizer_ignores_synchronized_update() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let clean = sanitizer.push(b"\x1B[?2026ha\nb\x1B[31mc");
+    assert_eq!(clean, b"a\nb\x1B[31mc".to_vec());
+}
+
+#[test]
+fn test_sanitizer_ignores_encoding_switch() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::text_only());
+    let clean = sanitizer.push("\x1B%@café".as_bytes());
+    assert_eq!(String::from_utf8(clean).unwrap(), "café");
+}
+
+#[test]
+fn test_sanitizer_ignores_vt52_switch() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    let clean = sanitizer.push(b"\x1B[?2l\x1BAx\x1B[31my");
+    assert_eq!(clean, b"x\x1B[31my".to_vec());
+}
+
+#[test]
+fn test_sanitizer_discards_replies() {
+    let mut sanitizer = Sanitizer::new(SanitizePolicy::default());
+    sanitizer.parser.set_answerback(b"hi");
+    let clean = sanitizer.push(b"\x05\x1BZ\x1B[c\x1B[6nok");
+    assert_eq!(clean, b"ok".to_vec());
+    assert!(sanitizer.parser.take_replies().is_empty());
+}
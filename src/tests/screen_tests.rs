@@ -0,0 +1,278 @@
+use crate::definitions::{Rect, TerminalOutput};
+use crate::{AnsiParser, LineSize, Screen};
+
+fn screen_with(columns: usize, rows: usize, input: &[u8]) -> Screen {
+    let mut screen = Screen::new(columns, rows);
+    feed(&mut screen, input);
+    screen
+}
+
+fn feed(screen: &mut Screen, input: &[u8]) {
+    let mut parser = AnsiParser::new();
+    for output in parser.push(input) {
+        screen.apply(&output);
+    }
+}
+
+fn lines(screen: &Screen) -> Vec<String> {
+    (0..screen.rows()).map(|row| screen.line_text(row)).collect()
+}
+
+#[test]
+fn test_print_wraps_and_scrolls() {
+    let screen = screen_with(4, 2, b"abcdefghij");
+    assert_eq!(lines(&screen), vec!["efgh", "ij"]);
+    assert_eq!(screen.cursor(), (2, 1));
+
+    // Without autowrap the last column is overwritten
+    let screen = screen_with(4, 2, b"\x1B[?7labcdef");
+    assert_eq!(lines(&screen), vec!["abcf", ""]);
+}
+
+#[test]
+fn test_scrolling_region() {
+    let screen = screen_with(3, 4, b"1\r\n2\r\n3\r\n4\x1B[2;3r\x1B[3H\nx");
+    assert_eq!(lines(&screen), vec!["1", "3", "x", "4"]);
+}
+
+#[test]
+fn test_zero_margins_mean_default() {
+    let mut screen = screen_with(3, 4, b"1\r\n2\r\n3\r\n4\x1B[3H");
+    screen.apply(&TerminalOutput::SetScrollingRegion { top: Some(0), bottom: Some(0) });
+    assert_eq!(screen.cursor(), (0, 0));
+    feed(&mut screen, b"\x1B[4H\nx");
+    assert_eq!(lines(&screen), vec!["2", "3", "4", "x"]);
+
+    let mut screen = screen_with(4, 1, b"\x1B[?69habcd\x1B[1;2H");
+    screen.apply(&TerminalOutput::SetLeftRightMargins { left: Some(0), right: Some(0) });
+    assert_eq!(screen.cursor(), (0, 0));
+    screen.apply(&TerminalOutput::SetLeftRightMargins { left: Some(0), right: Some(2) });
+    feed(&mut screen, b"\x1B[1;2H\x1B[P");
+    assert_eq!(lines(&screen), vec!["a cd"]);
+}
+
+#[test]
+fn test_fill_and_erase_rectangle() {
+    let mut screen = screen_with(5, 3, b"\x1B[42;1;2;2;4$x");
+    assert_eq!(lines(&screen), vec![" ***", " ***", ""]);
+    feed(&mut screen, b"\x1B[2;3;3;3$z");
+    assert_eq!(lines(&screen), vec![" ***", " * *", ""]);
+    // The cursor doesn't move
+    assert_eq!(screen.cursor(), (0, 0));
+}
+
+#[test]
+fn test_fill_rectangle_is_clamped_to_the_screen() {
+    let screen = screen_with(3, 2, b"\x1B[35;2;2;50;50$x");
+    assert_eq!(lines(&screen), vec!["", " ##"]);
+}
+
+#[test]
+fn test_copy_rectangle() {
+    let screen = screen_with(6, 3, b"abc\r\ndef\x1B[1;1;2;2;1;2;4;1$v");
+    assert_eq!(lines(&screen), vec!["abc", "defab", "   de"]);
+
+    // Overlapping copies read the source before writing
+    let screen = screen_with(6, 1, b"abcd\x1B[1;1;1;4;1;1;2;1$v");
+    assert_eq!(lines(&screen), vec!["aabcd"]);
+}
+
+#[test]
+fn test_rectangles_respect_origin_mode() {
+    let screen = screen_with(6, 4, b"\x1B[2;3r\x1B[?69h\x1B[2;5s\x1B[?6h\x1B[43$x");
+    assert_eq!(lines(&screen), vec!["", " ++++", " ++++", ""]);
+
+    let screen = screen_with(6, 4, b"\x1B[2;3r\x1B[?6h\x1B[45;2;1;9;9$x");
+    assert_eq!(lines(&screen), vec!["", "", "------", ""]);
+}
+
+#[test]
+fn test_change_and_reverse_rectangle_attributes() {
+    let mut screen = screen_with(4, 2, b"ab\x1B[1mcd\x1B[m\x1B[1;1;1;2;4;7$r");
+    let cell = |screen: &Screen, column| *screen.cell(column, 0).unwrap();
+    assert!(cell(&screen, 0).style.underline && cell(&screen, 0).style.reverse);
+    assert!(!cell(&screen, 0).style.bold);
+    assert!(cell(&screen, 2).style.bold && !cell(&screen, 2).style.underline);
+
+    feed(&mut screen, b"\x1B[1;1;1;4;1$t");
+    assert!(cell(&screen, 0).style.bold);
+    assert!(!cell(&screen, 2).style.bold);
+
+    // DECCARA with 0 clears every changeable attribute
+    feed(&mut screen, b"\x1B[1;1;1;4;0$r");
+    assert_eq!(cell(&screen, 0).style, Default::default());
+}
+
+#[test]
+fn test_selective_erase_rectangle_keeps_attributes() {
+    let screen = screen_with(4, 1, b"\x1B[7mab\x1B[1;1;1;1${");
+    assert_eq!(screen.line_text(0), " b");
+    assert!(screen.cell(0, 0).unwrap().style.reverse);
+}
+
+#[test]
+fn test_checksum_reply() {
+    let screen = screen_with(4, 2, b"ab\x1B[1mc");
+    let area = |right| Rect { top: 1, left: 1, bottom: Some(1), right: Some(right) };
+    // 0x61 + 0x62 = 0xC3, negated
+    assert_eq!(screen.checksum(&area(2)), 0xFF3D);
+    assert_eq!(screen.checksum_reply(3, &area(2)), b"\x1BP3!~FF3D\x1B\\");
+    // Bold adds 0x80 and blanks count as spaces
+    assert_eq!(screen.checksum(&area(4)), (0x61u16 + 0x62 + 0x63 + 0x80 + 0x20).wrapping_neg());
+}
+
+#[test]
+fn test_checksum_request_round_trip() {
+    let mut screen = screen_with(3, 3, b"xyz");
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B[1;1;1;1;1;3*y");
+    let TerminalOutput::RequestChecksum { id, area, .. } = &output[0] else { panic!("{:?}", output) };
+    let before = screen.checksum_reply(*id, area);
+    feed(&mut screen, b"\x1B[1;1;1;3$z");
+    assert_ne!(screen.checksum_reply(*id, area), before);
+}
+
+#[test]
+fn test_selective_erase_skips_protected_characters() {
+    let mut screen = screen_with(6, 2, b"ab\x1B[1\"qcd\x1B[0\"qef\r\nghij");
+    assert!(screen.cell(2, 0).unwrap().style.protected);
+    feed(&mut screen, b"\x1B[?2J");
+    assert_eq!(lines(&screen), vec!["  cd", ""]);
+
+    // Ordinary erases still clear DECSCA-protected characters
+    feed(&mut screen, b"\x1B[2J");
+    assert_eq!(lines(&screen), vec!["", ""]);
+}
+
+#[test]
+fn test_selective_erase_line_and_rectangle() {
+    let mut screen = screen_with(6, 1, b"a\x1B[1\"qb\x1B[\"qcdef\x1B[1;4H\x1B[?1K");
+    assert_eq!(screen.line_text(0), " b  ef");
+    feed(&mut screen, b"\x1B[1;1;1;6${");
+    assert_eq!(screen.line_text(0), " b");
+}
+
+#[test]
+fn test_guarded_area_protects_from_every_erase() {
+    let screen = screen_with(6, 1, b"a\x1BVbc\x1BWd\r\x1B[K");
+    assert_eq!(screen.line_text(0), " bc");
+    assert!(!screen.style().protected);
+}
+
+#[test]
+fn test_sgr_reset_keeps_protection() {
+    let screen = screen_with(2, 1, b"\x1B[1\"q\x1B[0ma");
+    assert!(screen.cell(0, 0).unwrap().style.protected);
+}
+
+#[test]
+fn test_full_reset() {
+    let mut screen = screen_with(4, 3, b"\x1B[2;3r\x1B[?6h\x1B[1\"qab\x1B7");
+    feed(&mut screen, b"\x1Bc");
+    assert_eq!(lines(&screen), vec!["", "", ""]);
+    assert_eq!(screen.cursor(), (0, 0));
+    assert_eq!(screen.style(), Default::default());
+    // Margins and origin mode are gone: row 3 is reachable and scrolls the whole screen
+    feed(&mut screen, b"\x1B[3Hx\n");
+    assert_eq!(lines(&screen), vec!["", "x", ""]);
+}
+
+#[test]
+fn test_soft_reset() {
+    let mut screen = screen_with(4, 3, b"\x1B[2;3r\x1B[?6h\x1B[7mab");
+    feed(&mut screen, b"\x1B[!p");
+    // Contents and the cursor stay
+    assert_eq!(lines(&screen), vec!["", "ab", ""]);
+    assert_eq!(screen.cursor(), (2, 1));
+    assert_eq!(screen.style(), Default::default());
+    // Autowrap is off and origin mode no longer limits addressing
+    feed(&mut screen, b"\x1B[3;1Hwxyz!");
+    assert_eq!(lines(&screen), vec!["", "ab", "wxy!"]);
+    // The saved cursor is home
+    feed(&mut screen, b"\x1B8");
+    assert_eq!(screen.cursor(), (0, 0));
+}
+
+#[test]
+fn test_screen_alignment_test() {
+    let mut screen = screen_with(3, 3, b"\x1B[2;3r\x1B#6\x1B[1;31mab");
+    feed(&mut screen, b"\x1B#8");
+    assert_eq!(lines(&screen), vec!["EEE", "EEE", "EEE"]);
+    assert_eq!(screen.cell(0, 0).unwrap().style, Default::default());
+    assert_eq!(screen.line_size(0), LineSize::SingleWidth);
+    assert_eq!(screen.cursor(), (0, 0));
+    // The scrolling region is the whole screen again
+    feed(&mut screen, b"\x1B[3H\nx");
+    assert_eq!(lines(&screen), vec!["EEE", "EEE", "x"]);
+}
+
+#[test]
+fn test_double_width_line_halves_width() {
+    let mut screen = screen_with(6, 2, b"abcdef\r\x1B#6");
+    assert_eq!(screen.line_size(0), LineSize::DoubleWidth);
+    assert_eq!(screen.line_width(0), 3);
+    // The right half is lost
+    assert_eq!(screen.line_text(0), "abc");
+
+    // Printing wraps at the middle of the screen
+    feed(&mut screen, b"xyzw");
+    assert_eq!(lines(&screen), vec!["xyz", "w"]);
+}
+
+#[test]
+fn test_double_size_line_clamps_cursor() {
+    let mut screen = screen_with(8, 2, b"\x1B[1;7H\x1B#3");
+    assert_eq!(screen.line_size(0), LineSize::DoubleHeightTop);
+    assert_eq!(screen.cursor(), (3, 0));
+    feed(&mut screen, b"\x1B[10C");
+    assert_eq!(screen.cursor(), (3, 0));
+    feed(&mut screen, b"\x1B[2;8H\x1BM");
+    assert_eq!(screen.cursor(), (3, 0));
+    // Single-width lines use the full width again
+    feed(&mut screen, b"\x1B#5\x1B[1;8H");
+    assert_eq!(screen.cursor(), (7, 0));
+}
+
+#[test]
+fn test_line_sizes_scroll_with_lines() {
+    let mut screen = screen_with(4, 2, b"\x1B#6a\r\n\n");
+    assert_eq!(screen.line_size(0), LineSize::SingleWidth);
+    assert_eq!(screen.line_size(1), LineSize::SingleWidth);
+    feed(&mut screen, b"\x1B[2H\x1B#6\n");
+    assert_eq!(screen.line_size(0), LineSize::DoubleWidth);
+
+    // Erasing the display resets line sizes
+    feed(&mut screen, b"\x1B[2J");
+    assert_eq!(screen.line_size(0), LineSize::SingleWidth);
+}
+
+#[test]
+fn test_horizontal_tab() {
+    let mut screen = screen_with(20, 1, b"a\tb\tc");
+    assert_eq!(screen.line_text(0), "a       b       c");
+    // The last column stops tabs
+    feed(&mut screen, b"\t\t\t");
+    assert_eq!(screen.cursor(), (19, 0));
+}
+
+#[test]
+fn test_saturated_params_in_origin_mode() {
+    let huge = b"99999999999999999999999";
+    let mut screen = screen_with(80, 24, b"\x1B[5;20r\x1B[?6h");
+    feed(&mut screen, &[b"\x1B[".as_slice(), huge, b";1H"].concat());
+    assert_eq!(screen.cursor(), (0, 19));
+    feed(&mut screen, &[b"\x1B[1;".as_slice(), huge, b"H"].concat());
+    assert_eq!(screen.cursor(), (79, 4));
+    feed(&mut screen, &[b"\x1B[".as_slice(), huge, b"B\x1B[", huge, b"C"].concat());
+    assert_eq!(screen.cursor(), (79, 19));
+
+    // Rectangles starting past the margins are empty; huge ends are clamped
+    feed(&mut screen, &[b"\x1B[".as_slice(), huge, b";1;3;3$z"].concat());
+    feed(&mut screen, &[b"\x1B[42;1;1;".as_slice(), huge, b";", huge, b"$x"].concat());
+    assert_eq!(screen.line_text(4), "*".repeat(80));
+    assert_eq!(screen.line_text(19), "*".repeat(80));
+    assert_eq!(screen.line_text(20), "");
+    feed(&mut screen, &[b"\x1B[1;1;1;1;1;".as_slice(), huge, b";", huge, b"$v"].concat());
+    let area = Rect { top: usize::MAX, left: 1, bottom: Some(usize::MAX), right: None };
+    assert_eq!(screen.checksum(&area), 0);
+}
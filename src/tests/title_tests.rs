@@ -0,0 +1,114 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::title::*;
+
+fn title(s: &str) -> TerminalOutput {
+    TerminalOutput::SetTitle(s.to_string())
+}
+
+fn icon(s: &str) -> TerminalOutput {
+    TerminalOutput::SetIconName(s.to_string())
+}
+
+#[test]
+fn test_osc_titles() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B]0;both\x07\x1B]1;icon\x07\x1B]2;title \xE2\x9C\x93\x1B\\\x1B]2;\x07");
+    assert_eq!(output, vec![icon("both"), title("both"), icon("icon"), title("title ✓"), title("")]);
+}
+
+#[test]
+fn test_title_stack_sequences() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B[22t\x1B[22;1t\x1B[22;2t\x1B[23;0t\x1B[23;2t\x1B[23;9t");
+    assert_eq!(output, vec![
+        TerminalOutput::PushTitle(TitleTarget::Both),
+        TerminalOutput::PushTitle(TitleTarget::IconName),
+        TerminalOutput::PushTitle(TitleTarget::Title),
+        TerminalOutput::PopTitle(TitleTarget::Both),
+        TerminalOutput::PopTitle(TitleTarget::Title),
+    ]);
+}
+
+#[test]
+fn test_title_modes() {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.title_modes(), TitleModes::default());
+
+    // Hex-encoded titles
+    assert!(parser.push(b"\x1B[>0;1t").is_empty());
+    assert_eq!(parser.title_modes(), TitleModes { set_hex: true, query_hex: true, ..Default::default() });
+    let output = parser.push(b"\x1B]2;48692021\x07");
+    assert_eq!(output, vec![title("Hi !")]);
+    // Malformed hex is left raw
+    let output = parser.push(b"\x1B]2;4G\x07");
+    assert_eq!(output, vec![TerminalOutput::Osc { command: 2, payload: b"4G".to_vec() }]);
+
+    parser.push(b"\x1B[>0T");
+    assert!(!parser.title_modes().set_hex);
+    assert!(parser.title_modes().query_hex);
+
+    // Invalid UTF-8 reads as Latin-1 unless UTF-8 titles are enabled
+    let output = parser.push(b"\x1B]2;caf\xE9\x07");
+    assert_eq!(output, vec![title("café")]);
+    parser.push(b"\x1B[>2t");
+    let output = parser.push(b"\x1B]2;caf\xE9\x07");
+    assert_eq!(output, vec![title("caf\u{FFFD}")]);
+}
+
+#[test]
+fn test_private_markers_are_not_ansi_sequences() {
+    let mut parser = AnsiParser::new();
+    // XTMODKEYS, not SGR underline + faint
+    assert!(parser.push(b"\x1B[>4;2m").is_empty());
+    // DEC private modes still work
+    assert_eq!(parser.push(b"\x1B[?25l"), vec![TerminalOutput::SetCursorVisibility(false)]);
+}
+
+#[test]
+fn test_title_stack() {
+    let mut stack = TitleStack::with_max_depth(2);
+    assert!(stack.apply(&title("one")));
+    assert!(stack.apply(&icon("i1")));
+    assert!(!stack.apply(&title("one")));
+
+    stack.apply(&TerminalOutput::PushTitle(TitleTarget::Both));
+    stack.apply(&title("two"));
+    stack.apply(&icon("i2"));
+
+    // Popping only the title leaves the icon name alone
+    assert!(stack.apply(&TerminalOutput::PopTitle(TitleTarget::Title)));
+    assert_eq!((stack.title(), stack.icon_name()), ("one", "i2"));
+
+    // An entry saved for the title only can't restore the icon name
+    stack.apply(&TerminalOutput::PushTitle(TitleTarget::Title));
+    stack.apply(&icon("i3"));
+    assert!(!stack.apply(&TerminalOutput::PopTitle(TitleTarget::Both)));
+    assert_eq!(stack.icon_name(), "i3");
+
+    // Nothing to pop
+    assert!(!stack.apply(&TerminalOutput::PopTitle(TitleTarget::Both)));
+}
+
+#[test]
+fn test_title_stack_depth_limit() {
+    let mut stack = TitleStack::with_max_depth(2);
+    for name in ["a", "b", "c", "d"] {
+        stack.apply(&title(name));
+        stack.apply(&TerminalOutput::PushTitle(TitleTarget::Title));
+    }
+    assert_eq!(stack.depth(), 2);
+    stack.apply(&TerminalOutput::PopTitle(TitleTarget::Title));
+    stack.apply(&TerminalOutput::PopTitle(TitleTarget::Title));
+    assert_eq!(stack.title(), "c");
+    assert_eq!(stack.depth(), 0);
+    assert_eq!(TitleStack::new().depth(), 0);
+}
+
+#[test]
+fn test_title_stack_default_matches_new() {
+    let mut stack = TitleStack::default();
+    assert_eq!(stack, TitleStack::new());
+    stack.apply(&TerminalOutput::PushTitle(TitleTarget::Both));
+    assert_eq!(stack.depth(), 1);
+}
@@ -0,0 +1,131 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+
+fn vt52_parser() -> AnsiParser {
+    let mut parser = AnsiParser::new();
+    assert_eq!(parser.push(b"\x1B[?2l"), vec![TerminalOutput::ResetMode(Mode::Ansi)]);
+    assert!(parser.is_vt52_mode());
+    parser
+}
+
+#[test]
+fn test_vt52_cursor_movement() {
+    let mut parser = vt52_parser();
+    let output = parser.push(b"\x1BA\x1BB\x1BC\x1BDx\x1BH\x1BI");
+    assert_eq!(output, vec![
+        TerminalOutput::CursorUp(1),
+        TerminalOutput::CursorDown(1),
+        TerminalOutput::CursorForward(1),
+        TerminalOutput::CursorBackward(1),
+        TerminalOutput::Data(b"x".to_vec()),
+        TerminalOutput::SetCursorPos { x: Some(1), y: Some(1) },
+        TerminalOutput::ReverseIndex,
+    ]);
+
+    // Direct cursor address: row and column offset by 0x20
+    let output = parser.push(b"\x1BY\x25\x2A");
+    assert_eq!(output, vec![TerminalOutput::SetCursorPos { x: Some(11), y: Some(6) }]);
+}
+
+#[test]
+fn test_vt52_cursor_address_split_across_pushes() {
+    let mut parser = vt52_parser();
+    assert_eq!(parser.push(b"\x1BY"), vec![]);
+    assert_eq!(parser.push(b" "), vec![]);
+    assert_eq!(parser.push(b"!"), vec![TerminalOutput::SetCursorPos { x: Some(2), y: Some(1) }]);
+}
+
+#[test]
+fn test_vt52_erase_and_keypad() {
+    let mut parser = vt52_parser();
+    let output = parser.push(b"\x1BJ\x1BK\x1B=\x1B>");
+    assert_eq!(output, vec![
+        TerminalOutput::ClearForwards,
+        TerminalOutput::ClearLineForwards,
+        TerminalOutput::SetMode(Mode::KeypadApplication),
+        TerminalOutput::ResetMode(Mode::KeypadApplication),
+    ]);
+}
+
+#[test]
+fn test_vt52_graphics_charset() {
+    let mut parser = vt52_parser();
+    let output = parser.push(b"\x1BFq\x1BGq");
+    assert_eq!(output, vec![TerminalOutput::Data("─q".as_bytes().to_vec())]);
+}
+
+#[test]
+fn test_vt52_graphics_leaves_g0_alone() {
+    let mut parser = AnsiParser::new();
+    parser.push(b"\x1B(A\x1B[?2l");
+    let output = parser.push(b"\x1BFq\x1B<#q");
+    assert_eq!(output, vec![
+        TerminalOutput::Data("─".as_bytes().to_vec()),
+        TerminalOutput::SetMode(Mode::Ansi),
+        TerminalOutput::Data("£q".as_bytes().to_vec()),
+    ]);
+}
+
+#[test]
+fn test_vt52_identify() {
+    let mut parser = vt52_parser();
+    assert_eq!(parser.push(b"\x1BZ"), vec![]);
+    assert_eq!(parser.take_replies(), b"\x1B/Z");
+}
+
+#[test]
+fn test_vt52_has_no_csi() {
+    let mut parser = vt52_parser();
+    // `ESC [` is not a VT52 sequence, so the parameters are plain text
+    let output = parser.push(b"\x1B[2J\r\n");
+    assert_eq!(output, vec![
+        TerminalOutput::Data(b"2J".to_vec()),
+        TerminalOutput::CarriageReturn,
+        TerminalOutput::Newline,
+    ]);
+}
+
+#[test]
+fn test_vt52_controls_inside_sequence() {
+    let mut parser = vt52_parser();
+    // C0 controls are executed without aborting the sequence; CAN aborts it
+    let output = parser.push(b"\x1BY\r !\x1BY\x18A");
+    assert_eq!(output, vec![
+        TerminalOutput::CarriageReturn,
+        TerminalOutput::SetCursorPos { x: Some(2), y: Some(1) },
+        TerminalOutput::Data(b"A".to_vec()),
+    ]);
+}
+
+#[test]
+fn test_vt52_exit() {
+    let mut parser = vt52_parser();
+    let output = parser.push(b"\x1B<\x1B[2A");
+    assert_eq!(output, vec![TerminalOutput::SetMode(Mode::Ansi), TerminalOutput::CursorUp(2)]);
+    assert!(!parser.is_vt52_mode());
+}
+
+#[test]
+fn test_vt52_mode_at_runtime() {
+    let mut parser = AnsiParser::new();
+    parser.set_vt52_mode(true);
+    assert_eq!(parser.push(b"\x1BA"), vec![TerminalOutput::CursorUp(1)]);
+    parser.set_vt52_mode(false);
+    assert_eq!(parser.push(b"\x1B[3A"), vec![TerminalOutput::CursorUp(3)]);
+}
+
+#[test]
+fn test_ansi_keypad_modes() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B=\x1B>\x1B[?66h\x1B[?66l\x1B[?2h");
+    assert_eq!(output, vec![
+        TerminalOutput::SetMode(Mode::KeypadApplication),
+        TerminalOutput::ResetMode(Mode::KeypadApplication),
+        TerminalOutput::SetMode(Mode::KeypadApplication),
+        TerminalOutput::ResetMode(Mode::KeypadApplication),
+        TerminalOutput::SetMode(Mode::Ansi),
+    ]);
+    // ANSI mode 2 (KAM) is not DECANM
+    assert_eq!(parser.push(b"\x1B[2l"), vec![TerminalOutput::ResetMode(Mode::Unknown(b"2".to_vec()))]);
+    assert!(!parser.is_vt52_mode());
+}
@@ -0,0 +1,88 @@
+use crate::AnsiParser;
+use crate::definitions::*;
+use crate::title::TitleModes;
+use crate::window::*;
+
+fn op(op: WindowOp) -> TerminalOutput {
+    TerminalOutput::WindowOp(op)
+}
+
+#[test]
+fn test_size_reports_allowed_by_default() {
+    let mut parser = AnsiParser::new();
+    let output = parser.push(b"\x1B[14t\x1B[14;2t\x1B[16t\x1B[18t\x1B[13;2t\x1B[11t");
+    assert_eq!(output, vec![
+        op(WindowOp::ReportSizePixels { text_area: true }),
+        op(WindowOp::ReportSizePixels { text_area: false }),
+        op(WindowOp::ReportCellSizePixels),
+        op(WindowOp::ReportSizeChars),
+        op(WindowOp::ReportPosition { text_area: true }),
+        op(WindowOp::ReportState),
+    ]);
+
+    // Moves, resizes and title reports are denied
+    assert!(parser.push(b"\x1B[2t\x1B[3;10;20t\x1B[8;24;80t\x1B[9;1t\x1B[21t\x1B[30t").is_empty());
+}
+
+#[test]
+fn test_window_op_policy() {
+    let mut parser = AnsiParser::new();
+    parser.set_window_op_policy(|op| !matches!(op, WindowOp::ReportTitle | WindowOp::ReportIconName));
+    let output = parser.push(b"\x1B[2t\x1B[3;10;20t\x1B[4;;640t\x1B[8;24;80t\x1B[9;3t\x1B[10;2t\x1B[48t\x1B[21t");
+    assert_eq!(output, vec![
+        op(WindowOp::Iconify),
+        op(WindowOp::Move { x: 10, y: 20 }),
+        op(WindowOp::ResizePixels { width: Some(640), height: None }),
+        op(WindowOp::ResizeChars { columns: Some(80), rows: Some(24) }),
+        op(WindowOp::Maximize(MaximizeMode::Horizontally)),
+        op(WindowOp::Fullscreen(FullscreenMode::Toggle)),
+        op(WindowOp::ResizeLines(48)),
+    ]);
+
+    // Unknown operations and modes are ignored; 22/23 are still title stack ops
+    let output = parser.push(b"\x1B[12t\x1B[9;7t\x1B[22;2t");
+    assert_eq!(output, vec![TerminalOutput::PushTitle(TitleTarget::Title)]);
+}
+
+#[test]
+fn test_window_op_replies() {
+    let state = WindowState {
+        columns: 80,
+        rows: 24,
+        cell_width: 9,
+        cell_height: 18,
+        x: 100,
+        y: 50,
+        padding_x: 2,
+        padding_y: 3,
+        screen_width: 1920,
+        screen_height: 1080,
+        title: "vim ✓".to_string(),
+        icon_name: "vim".to_string(),
+        ..Default::default()
+    };
+    let modes = TitleModes::default();
+    let reply = |o: WindowOp| window_op_reply(&o, &state, modes).map(|r| String::from_utf8_lossy(&r).into_owned());
+
+    assert_eq!(reply(WindowOp::ReportState).as_deref(), Some("\x1B[1t"));
+    assert_eq!(reply(WindowOp::ReportPosition { text_area: false }).as_deref(), Some("\x1B[3;100;50t"));
+    assert_eq!(reply(WindowOp::ReportPosition { text_area: true }).as_deref(), Some("\x1B[3;102;53t"));
+    assert_eq!(reply(WindowOp::ReportSizePixels { text_area: true }).as_deref(), Some("\x1B[4;432;720t"));
+    assert_eq!(reply(WindowOp::ReportSizePixels { text_area: false }).as_deref(), Some("\x1B[4;438;724t"));
+    assert_eq!(reply(WindowOp::ReportScreenSizePixels).as_deref(), Some("\x1B[5;1080;1920t"));
+    assert_eq!(reply(WindowOp::ReportCellSizePixels).as_deref(), Some("\x1B[6;18;9t"));
+    assert_eq!(reply(WindowOp::ReportSizeChars).as_deref(), Some("\x1B[8;24;80t"));
+    assert_eq!(reply(WindowOp::ReportScreenSizeChars).as_deref(), Some("\x1B[9;60;213t"));
+    assert_eq!(reply(WindowOp::ReportIconName).as_deref(), Some("\x1B]Lvim\x1B\\"));
+    assert_eq!(reply(WindowOp::Raise), None);
+}
+
+#[test]
+fn test_title_report_modes() {
+    let state = WindowState { title: "é✓".to_string(), ..Default::default() };
+    let report = |modes| window_op_reply(&WindowOp::ReportTitle, &state, modes).unwrap();
+
+    assert_eq!(report(TitleModes::default()), b"\x1B]l\xE9?\x1B\\");
+    assert_eq!(report(TitleModes { query_utf8: true, ..Default::default() }), "\x1B]lé✓\x1B\\".as_bytes());
+    assert_eq!(report(TitleModes { query_hex: true, ..Default::default() }), b"\x1B]lE93F\x1B\\");
+}
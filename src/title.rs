@@ -0,0 +1,141 @@
+use crate::definitions::TerminalOutput;
+
+/// Depth of xterm's title stack
+pub const DEFAULT_TITLE_STACK_DEPTH: usize = 10;
+
+/// xterm title modes, changed with `CSI > Ps t` (set) and `CSI > Ps T` (reset).
+/// Each field is named after its `Ps`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TitleModes {
+    /// `0`: OSC 0/1/2 titles are sent hex-encoded
+    pub set_hex: bool,
+    /// `1`: title reports are sent hex-encoded
+    pub query_hex: bool,
+    /// `2`: OSC 0/1/2 titles are always UTF-8
+    pub set_utf8: bool,
+    /// `3`: title reports are sent as UTF-8
+    pub query_utf8: bool,
+}
+
+impl TitleModes {
+    pub(crate) fn apply(&mut self, params: &[usize], enable: bool) {
+        for &param in params {
+            match param {
+                0 => self.set_hex = enable,
+                1 => self.query_hex = enable,
+                2 => self.set_utf8 = enable,
+                3 => self.query_utf8 = enable,
+                _ => {}
+            }
+        }
+    }
+
+    /// Decode an OSC 0/1/2 payload. Outside UTF-8 mode, bytes that aren't
+    /// valid UTF-8 are read as Latin-1. `None` for malformed hex.
+    pub(crate) fn decode(&self, payload: &[u8]) -> Option<String> {
+        let hex;
+        let bytes = if self.set_hex {
+            hex = hex_decode(payload)?;
+            &hex[..]
+        } else {
+            payload
+        };
+        Some(match std::str::from_utf8(bytes) {
+            Ok(title) => title.to_string(),
+            Err(_) if self.set_utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Err(_) => bytes.iter().map(|&b| b as char).collect(),
+        })
+    }
+}
+
+fn hex_decode(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Tracks the window title and icon name, including the stack used by
+/// `CSI 22 t` / `CSI 23 t`. When the stack is full the oldest entry is dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleStack {
+    title: String,
+    icon_name: String,
+    stack: Vec<(Option<String>, Option<String>)>,
+    max_depth: usize,
+}
+
+impl Default for TitleStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TitleStack {
+    pub fn new() -> TitleStack {
+        Self::with_max_depth(DEFAULT_TITLE_STACK_DEPTH)
+    }
+
+    pub fn with_max_depth(max_depth: usize) -> TitleStack {
+        TitleStack {
+            title: String::new(),
+            icon_name: String::new(),
+            stack: Vec::new(),
+            max_depth,
+        }
+    }
+
+    /// Update from one parser output. Returns whether the title or icon name changed.
+    pub fn apply(&mut self, output: &TerminalOutput) -> bool {
+        match output {
+            TerminalOutput::SetTitle(title) => replace(&mut self.title, title.clone()),
+            TerminalOutput::SetIconName(name) => replace(&mut self.icon_name, name.clone()),
+            TerminalOutput::PushTitle(target) => {
+                if self.max_depth == 0 {
+                    return false;
+                }
+                if self.stack.len() == self.max_depth {
+                    self.stack.remove(0);
+                }
+                let title = target.includes_title().then(|| self.title.clone());
+                let icon_name = target.includes_icon_name().then(|| self.icon_name.clone());
+                self.stack.push((title, icon_name));
+                false
+            }
+            TerminalOutput::PopTitle(target) => {
+                let Some((title, icon_name)) = self.stack.pop() else {
+                    return false;
+                };
+                let mut changed = false;
+                if let Some(title) = title.filter(|_| target.includes_title()) {
+                    changed |= replace(&mut self.title, title);
+                }
+                if let Some(icon_name) = icon_name.filter(|_| target.includes_icon_name()) {
+                    changed |= replace(&mut self.icon_name, icon_name);
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn icon_name(&self) -> &str {
+        &self.icon_name
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+fn replace(slot: &mut String, value: String) -> bool {
+    let changed = *slot != value;
+    *slot = value;
+    changed
+}
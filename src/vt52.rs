@@ -0,0 +1,96 @@
+use log::warn;
+
+use crate::definitions::{Mode, TerminalOutput};
+
+/// Reply to VT52 `ESC Z` (identify): "I am a VT52 without a printer"
+pub(crate) const VT52_IDENTIFY_REPLY: &[u8] = b"\x1B/Z";
+
+/// VT52 grammar, used between DECANM reset (`CSI ? 2 l`) and `ESC <`.
+/// There are no CSI, OSC or DCS sequences, only `ESC x` and `ESC Y row col`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Vt52State {
+    #[default]
+    Ground,
+    Escape,
+    CursorRow,
+    CursorColumn(u8),
+}
+
+/// What the parser should do with a byte read in VT52 mode
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Vt52Action {
+    None,
+    Print(u8),
+    Execute(u8),
+    Dispatch(TerminalOutput),
+    /// `ESC F` / `ESC G`: enter or leave the graphics character set
+    Graphics(bool),
+    /// `ESC Z`
+    Identify,
+    /// `ESC <`
+    ExitVt52,
+}
+
+impl Vt52State {
+    pub(crate) fn advance(&mut self, byte: u8) -> Vt52Action {
+        match (*self, byte) {
+            (_, 0x1B) => {
+                *self = Vt52State::Escape;
+                Vt52Action::None
+            }
+            // CAN and SUB abort a sequence
+            (_, 0x18 | 0x1A) => {
+                *self = Vt52State::Ground;
+                Vt52Action::None
+            }
+            // Other controls are executed without interrupting the sequence
+            (_, 0x00..=0x1F | 0x7F) => Vt52Action::Execute(byte),
+            (Vt52State::Ground, _) => Vt52Action::Print(byte),
+            (Vt52State::Escape, _) => {
+                *self = Vt52State::Ground;
+                self.escape(byte)
+            }
+            (Vt52State::CursorRow, _) => {
+                *self = Vt52State::CursorColumn(byte);
+                Vt52Action::None
+            }
+            (Vt52State::CursorColumn(row), _) => {
+                *self = Vt52State::Ground;
+                // Coordinates are sent offset by 0x20, with 0x20 meaning the first line/column
+                Vt52Action::Dispatch(TerminalOutput::SetCursorPos {
+                    x: Some(usize::from(byte - 0x1F)),
+                    y: Some(usize::from(row - 0x1F)),
+                })
+            }
+        }
+    }
+
+    fn escape(&mut self, byte: u8) -> Vt52Action {
+        let item = match byte {
+            b'A' => TerminalOutput::CursorUp(1),
+            b'B' => TerminalOutput::CursorDown(1),
+            b'C' => TerminalOutput::CursorForward(1),
+            b'D' => TerminalOutput::CursorBackward(1),
+            b'H' => TerminalOutput::SetCursorPos { x: Some(1), y: Some(1) },
+            // Reverse line feed
+            b'I' => TerminalOutput::ReverseIndex,
+            b'J' => TerminalOutput::ClearForwards,
+            b'K' => TerminalOutput::ClearLineForwards,
+            b'=' => TerminalOutput::SetMode(Mode::KeypadApplication),
+            b'>' => TerminalOutput::ResetMode(Mode::KeypadApplication),
+            b'F' => return Vt52Action::Graphics(true),
+            b'G' => return Vt52Action::Graphics(false),
+            b'Y' => {
+                *self = Vt52State::CursorRow;
+                return Vt52Action::None;
+            }
+            b'Z' => return Vt52Action::Identify,
+            b'<' => return Vt52Action::ExitVt52,
+            _ => {
+                warn!("Unknown VT52 sequence: ESC {}", byte as char);
+                return Vt52Action::None;
+            }
+        };
+        Vt52Action::Dispatch(item)
+    }
+}
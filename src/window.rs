@@ -0,0 +1,132 @@
+use crate::definitions::{FullscreenMode, MaximizeMode, WindowOp};
+use crate::title::TitleModes;
+
+/// Decides which XTWINOPS requests are reported. The default allows only
+/// [`WindowOp::is_safe`] operations.
+pub type WindowOpPolicy = Box<dyn Fn(&WindowOp) -> bool + Send>;
+
+/// `CSI Ps ; Ps ; Ps t` parameters, except the title stack operations (22/23)
+pub(crate) fn parse_window_op(params: &[usize]) -> Option<WindowOp> {
+    let param = |index: usize| params.get(index).copied().unwrap_or(0);
+    let size = |index: usize| Some(param(index)).filter(|&size| size != 0);
+    let op = match param(0) {
+        1 => WindowOp::Deiconify,
+        2 => WindowOp::Iconify,
+        3 => WindowOp::Move { x: param(1), y: param(2) },
+        4 => WindowOp::ResizePixels { width: size(2), height: size(1) },
+        5 => WindowOp::Raise,
+        6 => WindowOp::Lower,
+        7 => WindowOp::Refresh,
+        8 => WindowOp::ResizeChars { columns: size(2), rows: size(1) },
+        9 => WindowOp::Maximize(match param(1) {
+            0 => MaximizeMode::Restore,
+            1 => MaximizeMode::Both,
+            2 => MaximizeMode::Vertically,
+            3 => MaximizeMode::Horizontally,
+            _ => return None,
+        }),
+        10 => WindowOp::Fullscreen(match param(1) {
+            0 => FullscreenMode::Exit,
+            1 => FullscreenMode::Enter,
+            2 => FullscreenMode::Toggle,
+            _ => return None,
+        }),
+        11 => WindowOp::ReportState,
+        13 => WindowOp::ReportPosition { text_area: param(1) == 2 },
+        14 => WindowOp::ReportSizePixels { text_area: param(1) != 2 },
+        15 => WindowOp::ReportScreenSizePixels,
+        16 => WindowOp::ReportCellSizePixels,
+        18 => WindowOp::ReportSizeChars,
+        19 => WindowOp::ReportScreenSizeChars,
+        20 => WindowOp::ReportIconName,
+        21 => WindowOp::ReportTitle,
+        lines @ 24.. => WindowOp::ResizeLines(lines),
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// What the embedder knows about its window, used to answer XTWINOPS reports.
+/// Sizes and positions are in pixels unless noted otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowState {
+    /// Text area size in characters
+    pub columns: usize,
+    pub rows: usize,
+    pub cell_width: usize,
+    pub cell_height: usize,
+    /// Position of the window on the screen
+    pub x: usize,
+    pub y: usize,
+    /// Offset of the text area inside the window
+    pub padding_x: usize,
+    pub padding_y: usize,
+    /// Outer window size; `0` means the text area plus padding on both sides
+    pub window_width: usize,
+    pub window_height: usize,
+    pub screen_width: usize,
+    pub screen_height: usize,
+    pub iconified: bool,
+    pub title: String,
+    pub icon_name: String,
+}
+
+impl WindowState {
+    fn text_area_pixels(&self) -> (usize, usize) {
+        (self.columns * self.cell_width, self.rows * self.cell_height)
+    }
+
+    fn window_pixels(&self) -> (usize, usize) {
+        let (width, height) = self.text_area_pixels();
+        let width = if self.window_width == 0 { width + 2 * self.padding_x } else { self.window_width };
+        let height = if self.window_height == 0 { height + 2 * self.padding_y } else { self.window_height };
+        (width, height)
+    }
+}
+
+/// The reply xterm sends for a report request, or `None` for operations that
+/// don't report anything. Title reports are encoded according to the query
+/// title modes.
+pub fn window_op_reply(op: &WindowOp, state: &WindowState, title_modes: TitleModes) -> Option<Vec<u8>> {
+    let reply = match *op {
+        WindowOp::ReportState => format!("\x1B[{}t", if state.iconified { 2 } else { 1 }),
+        WindowOp::ReportPosition { text_area: false } => format!("\x1B[3;{};{}t", state.x, state.y),
+        WindowOp::ReportPosition { text_area: true } => {
+            format!("\x1B[3;{};{}t", state.x + state.padding_x, state.y + state.padding_y)
+        }
+        WindowOp::ReportSizePixels { text_area } => {
+            let (width, height) = if text_area { state.text_area_pixels() } else { state.window_pixels() };
+            format!("\x1B[4;{};{}t", height, width)
+        }
+        WindowOp::ReportScreenSizePixels => format!("\x1B[5;{};{}t", state.screen_height, state.screen_width),
+        WindowOp::ReportCellSizePixels => format!("\x1B[6;{};{}t", state.cell_height, state.cell_width),
+        WindowOp::ReportSizeChars => format!("\x1B[8;{};{}t", state.rows, state.columns),
+        WindowOp::ReportScreenSizeChars => {
+            let rows = state.screen_height.checked_div(state.cell_height).unwrap_or(state.rows);
+            let columns = state.screen_width.checked_div(state.cell_width).unwrap_or(state.columns);
+            format!("\x1B[9;{};{}t", rows, columns)
+        }
+        WindowOp::ReportIconName => return Some(title_report(b'L', &state.icon_name, title_modes)),
+        WindowOp::ReportTitle => return Some(title_report(b'l', &state.title, title_modes)),
+        _ => return None,
+    };
+    Some(reply.into_bytes())
+}
+
+/// `OSC L label ST` or `OSC l label ST`. Without UTF-8 query mode the label
+/// is sent as Latin-1, with `?` for characters outside it.
+fn title_report(kind: u8, label: &str, title_modes: TitleModes) -> Vec<u8> {
+    let bytes: Vec<u8> = if title_modes.query_utf8 {
+        label.as_bytes().to_vec()
+    } else {
+        label.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect()
+    };
+    let mut reply = vec![0x1B, b']', kind];
+    if title_modes.query_hex {
+        reply.extend(bytes.iter().flat_map(|b| format!("{:02X}", b).into_bytes()));
+    } else {
+        reply.extend(bytes);
+    }
+    reply.extend_from_slice(b"\x1B\\");
+    reply
+}